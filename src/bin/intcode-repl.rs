@@ -0,0 +1,409 @@
+use advent2019::intcode::{assemble, disassemble, disassemble_one, Computer, Memory, Word};
+use std::collections::VecDeque;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+const USAGE: &str = "usage: intcode-repl [PROGRAM]
+
+Starts an interactive session on PROGRAM, or on empty memory. Type help
+at the prompt for the commands.";
+
+const HELP: &str = "Anything that isn't a command is written at the pc and executed until the
+pc leaves it, either as assembler (add [4], 3, [rb-1]; out 7) or as raw
+comma separated words (1101,2,3,100).
+
+commands:
+  mem A[..B]         show memory, 8 cells to a row
+  dis [A[..B]]       disassemble, from the pc by default
+  set ADDR N,N,...   write words to memory starting at ADDR
+  pc [N]             show or move the pc
+  rb [N]             show or set the relative base
+  input N,N,...      queue inputs for the program
+  out                show everything output so far
+  step [N]           execute N instructions (default 1)
+  run [N]            run until the program halts or needs input
+  state              show the pc, relative base and queued inputs
+  reset              go back to the memory the session started with
+  load PATH          start over on another program
+  help               show this
+  quit               leave";
+
+// stops runaway loops in snippets and `run`
+const MAX_STEPS: u64 = 1_000_000;
+
+fn fail(message: &str) -> ! {
+    eprintln!("intcode-repl: {}", message);
+    process::exit(1);
+}
+
+fn number<T: std::str::FromStr>(text: &str, what: &str) -> Result<T, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("bad {}: {:?}", what, text.trim()))
+}
+
+fn address(text: &str) -> Result<Word, String> {
+    let addr = number(text, "address")?;
+    if addr < 0 {
+        return Err(format!("negative address {}", addr));
+    }
+    Ok(addr)
+}
+
+fn too_large(addr: Word) -> String {
+    format!("address {} is too large", addr)
+}
+
+fn words(text: &str) -> Result<Vec<Word>, String> {
+    text.split(',').map(|n| number(n, "number")).collect()
+}
+
+// `A..B` or just `A`, which means `len` cells from A
+fn range(text: &str, len: Word) -> Result<(Word, Word), String> {
+    match text.find("..") {
+        Some(i) => Ok((address(&text[..i])?, address(&text[i + 2..])?)),
+        None => {
+            let start = address(text)?;
+            Ok((
+                start,
+                start.checked_add(len).ok_or_else(|| too_large(start))?,
+            ))
+        }
+    }
+}
+
+struct Repl {
+    program: Memory,
+    computer: Computer,
+    inputs: VecDeque<Word>,
+    outputs: Vec<Word>,
+}
+
+impl Repl {
+    fn new(program: Memory) -> Repl {
+        Repl {
+            computer: Computer::new(program.clone()),
+            program,
+            inputs: VecDeque::new(),
+            outputs: vec![],
+        }
+    }
+
+    /// Runs until `done` says to stop or the machine can't go on, printing
+    /// outputs as they appear.
+    fn run(&mut self, done: impl Fn(&Computer, u64) -> bool) -> Result<(), String> {
+        let mut steps = 0;
+        let result = loop {
+            if done(&self.computer, steps) {
+                break Ok(());
+            }
+            if steps == MAX_STEPS {
+                break Err(format!("stopped after {} steps", steps));
+            }
+            match self.computer.step() {
+                Ok(true) => steps += 1,
+                Ok(false) if self.computer.halted() => {
+                    println!("halted");
+                    break Ok(());
+                }
+                Ok(false) => match self.inputs.pop_front() {
+                    Some(input) => self.computer.input(input),
+                    None => {
+                        println!("waiting for input");
+                        break Ok(());
+                    }
+                },
+                Err(trap) => break Err(trap.to_string()),
+            }
+            for output in self.computer.outputs.drain(..) {
+                println!("<- {}", output);
+                self.outputs.push(output);
+            }
+        };
+        for output in self.computer.outputs.drain(..) {
+            println!("<- {}", output);
+            self.outputs.push(output);
+        }
+        result
+    }
+
+    /// Writes `code` at the pc and executes it until the pc leaves it.
+    fn execute(&mut self, code: &[Word]) -> Result<(), String> {
+        let start = self.computer.pc();
+        let end = start
+            .checked_add(code.len() as Word)
+            .ok_or_else(|| too_large(start))?;
+        for (addr, word) in (start..end).zip(code) {
+            self.computer.memory.write(addr, *word);
+        }
+        // something new to run over a hlt
+        self.computer.set_pc(start);
+        self.run(|computer, steps| steps > 0 && !(start..end).contains(&computer.pc()))
+    }
+
+    fn show_memory(&self, start: Word, end: Word) {
+        let mut addr = start;
+        while addr < end {
+            let row: Vec<String> = (addr..end.min(addr.saturating_add(8)))
+                .map(|addr| self.computer.memory.peek(addr).to_string())
+                .collect();
+            println!("{:>6}: {}", addr, row.join(" "));
+            addr = match addr.checked_add(8) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+
+    fn show_state(&self) {
+        let inputs: Vec<String> = self.inputs.iter().map(Word::to_string).collect();
+        println!(
+            "pc={} rb={}{} inputs=[{}] outputs={}",
+            self.computer.pc(),
+            self.computer.relative_base(),
+            if self.computer.halted() {
+                " halted"
+            } else {
+                ""
+            },
+            inputs.join(","),
+            self.outputs.len()
+        );
+        if let Some((text, _)) = disassemble_one(&self.computer.memory, self.computer.pc()) {
+            println!("{:>6}: {}", self.computer.pc(), text);
+        }
+    }
+
+    /// Handles one line. Returns false to quit.
+    fn line(&mut self, line: &str) -> Result<bool, String> {
+        let line = line.trim();
+        let (command, arg) = match line.find(char::is_whitespace) {
+            Some(i) => (&line[..i], line[i..].trim()),
+            None => (line, ""),
+        };
+        match (command, arg) {
+            ("", _) => (),
+            ("quit", _) | ("exit", _) => return Ok(false),
+            ("help", _) => println!("{}", HELP),
+            ("mem", "") => return Err("mem needs an address or a range".to_string()),
+            ("mem", arg) => {
+                let (start, end) = range(arg, 1)?;
+                self.show_memory(start, end);
+            }
+            ("dis", arg) => {
+                let (start, end) = if arg.is_empty() {
+                    (self.computer.pc(), self.computer.pc().saturating_add(20))
+                } else {
+                    range(arg, 20)?
+                };
+                for (addr, text) in disassemble(&self.computer.memory, start, end) {
+                    let marker = if addr == self.computer.pc() { ">" } else { " " };
+                    println!("{}{:>5}: {}", marker, addr, text);
+                }
+            }
+            ("set", arg) => {
+                let mut parts = arg.splitn(2, char::is_whitespace);
+                let start = address(parts.next().unwrap())?;
+                let values = match parts.next() {
+                    Some(values) => words(values)?,
+                    None => return Err("set needs an address and some words".to_string()),
+                };
+                let end = start
+                    .checked_add(values.len() as Word)
+                    .ok_or_else(|| too_large(start))?;
+                for (addr, value) in (start..end).zip(values) {
+                    self.computer.memory.write(addr, value);
+                }
+            }
+            ("pc", "") => println!("{}", self.computer.pc()),
+            ("pc", arg) => self.computer.set_pc(address(arg)?),
+            ("rb", "") => println!("{}", self.computer.relative_base()),
+            ("rb", arg) => self
+                .computer
+                .set_relative_base(number(arg, "relative base")?),
+            ("input", arg) => self.inputs.extend(words(arg)?),
+            ("out", "") => {
+                let outputs: Vec<String> = self.outputs.iter().map(Word::to_string).collect();
+                println!("{}", outputs.join(","));
+            }
+            ("step", arg) => {
+                let count = if arg.is_empty() {
+                    1
+                } else {
+                    number(arg, "step count")?
+                };
+                self.run(|_, steps| steps == count)?;
+                self.show_state();
+            }
+            ("run", arg) => {
+                let limit = if arg.is_empty() {
+                    None
+                } else {
+                    Some(number(arg, "step count")?)
+                };
+                self.run(|_, steps| Some(steps) == limit)?;
+            }
+            ("state", "") => self.show_state(),
+            ("reset", "") => *self = Repl::new(self.program.clone()),
+            ("load", path) if !path.is_empty() => match Memory::load_file(path) {
+                Ok(program) => *self = Repl::new(program),
+                Err(error) => return Err(format!("{}: {}", path, error)),
+            },
+            _ if line.starts_with(|c: char| c == '-' || c.is_ascii_digit()) => {
+                self.execute(&words(line)?)?
+            }
+            _ => {
+                let code = assemble(line).map_err(|error| error.message)?;
+                self.execute(&code)?
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let program = match args.as_slice() {
+        [] => Memory::empty(),
+        [flag] if flag == "-h" || flag == "--help" => {
+            println!("{}", USAGE);
+            return;
+        }
+        [path] => match Memory::load_file(path) {
+            Ok(memory) => memory,
+            Err(error) => fail(&format!("{}: {}", path, error)),
+        },
+        _ => fail(USAGE),
+    };
+    let mut repl = Repl::new(program);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        if io::stdout().flush().is_err() {
+            process::exit(1);
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(error)) => fail(&format!("stdin: {}", error)),
+            None => break,
+        };
+        match repl.line(&line) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(message) => println!("error: {}", message),
+        }
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peek(repl: &Repl, addrs: std::ops::Range<Word>) -> Vec<Word> {
+        addrs.map(|addr| repl.computer.memory.peek(addr)).collect()
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut repl = Repl::new(Memory::empty());
+        assert_eq!(Ok(true), repl.line("set 10 5,6"));
+        assert_eq!(Ok(true), repl.line("add [10], [11], [12]"));
+        assert_eq!(vec![5, 6, 11], peek(&repl, 10..13));
+        assert_eq!(4, repl.computer.pc());
+
+        assert_eq!(Ok(true), repl.line("input 7,8"));
+        assert_eq!(Ok(true), repl.line("in [13]; out [13]"));
+        assert_eq!(vec![7], repl.outputs);
+        assert_eq!(vec![8], Vec::from(repl.inputs.clone()));
+
+        // raw words, over what's at the pc
+        assert_eq!(Ok(true), repl.line("pc 20"));
+        assert_eq!(Ok(true), repl.line("1101,2,3,14"));
+        assert_eq!(5, repl.computer.memory.peek(14));
+        assert_eq!(24, repl.computer.pc());
+        assert_eq!(Ok(true), repl.line("rb -3"));
+        assert_eq!(-3, repl.computer.relative_base());
+
+        // the rest of the input, then hlt
+        assert_eq!(Ok(true), repl.line("set 24 3,30,4,30,99"));
+        assert_eq!(Ok(true), repl.line("run"));
+        assert!(repl.computer.halted());
+        assert_eq!(vec![7, 8], repl.outputs);
+
+        assert_eq!(Ok(true), repl.line("reset"));
+        assert_eq!(vec![0, 0, 0], peek(&repl, 10..13));
+        assert_eq!((0, 0), (repl.computer.pc(), repl.outputs.len()));
+        assert_eq!(Ok(false), repl.line("quit"));
+    }
+
+    #[test]
+    fn test_last_addresses() {
+        // an add whose operands would run off the end is only data
+        let mut repl = Repl::new(Memory::empty());
+        assert_eq!(Ok(true), repl.line("set 9223372036854775805 1,2"));
+        assert_eq!(
+            Ok(true),
+            repl.line("mem 9223372036854775800..9223372036854775807")
+        );
+        assert_eq!(
+            Ok(true),
+            repl.line("dis 9223372036854775800..9223372036854775807")
+        );
+        assert_eq!(Ok(true), repl.line("pc 9223372036854775806"));
+        assert_eq!(Ok(true), repl.line("dis"));
+        assert_eq!(
+            Err("address 9223372036854775806 is too large".to_string()),
+            repl.line("out 1")
+        );
+    }
+
+    #[test]
+    fn test_step_and_run_limits() {
+        // an endless loop
+        let mut repl = Repl::new(Memory::empty());
+        assert_eq!(Ok(true), repl.line("set 0 1105,1,0"));
+        assert_eq!(Ok(true), repl.line("step 3"));
+        assert_eq!(0, repl.computer.pc());
+        assert_eq!(Ok(true), repl.line("run 10"));
+        assert_eq!(
+            Err(format!("stopped after {} steps", MAX_STEPS)),
+            repl.line("run")
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut repl = Repl::new(Memory::empty());
+        let error = |repl: &mut Repl, line| repl.line(line).unwrap_err();
+        assert_eq!("unknown instruction \"nop\"", error(&mut repl, "nop"));
+        assert_eq!("jt takes 2 operands, not 1", error(&mut repl, "jt 1"));
+        assert_eq!(
+            "can't write to an immediate: \"5\"",
+            error(&mut repl, "in 5")
+        );
+        assert_eq!("bad number: \"x\"", error(&mut repl, "1,x"));
+        assert_eq!("negative address -1", error(&mut repl, "mem -1"));
+        assert_eq!("bad address: \"a\"", error(&mut repl, "mem a..4"));
+        assert_eq!("mem needs an address or a range", error(&mut repl, "mem"));
+        assert_eq!(
+            "set needs an address and some words",
+            error(&mut repl, "set 4")
+        );
+        assert_eq!("bad step count: \"many\"", error(&mut repl, "step many"));
+        assert_eq!(
+            "address 9223372036854775807 is too large",
+            error(&mut repl, "mem 9223372036854775807")
+        );
+        assert_eq!(
+            "address 9223372036854775806 is too large",
+            error(&mut repl, "set 9223372036854775806 1,2")
+        );
+        // nothing was run
+        assert_eq!(0, repl.computer.pc());
+        assert_eq!(vec![0; 4], peek(&repl, 0..4));
+    }
+}
@@ -0,0 +1,281 @@
+use advent2019::intcode::{
+    disassemble_one, Computer, Coverage, Format, Memory, Modifications, Word,
+};
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+const USAGE: &str = "usage: intcode PROGRAM [options]
+
+PROGRAM is a path to a text or .bin image, or - for stdin.
+
+options:
+  -i, --input N,N,...    feed these inputs (may be repeated)
+  --input-file PATH      feed the inputs in PATH
+  --stdin                feed the inputs on stdin
+  --ascii                read inputs as text and print outputs as text
+  -p, --patch ADDR=VAL   write VAL to ADDR before starting (may be repeated)
+  --max-steps N          stop after N instructions
+  --trace                print every instruction to stderr as it executes
+  --dump                 print memory after the program stops
+  --coverage             print an annotated disassembly to stderr afterwards
+  --self-modifying       report writes into executed code to stderr afterwards";
+
+struct Options {
+    program: String,
+    inputs: Vec<String>,
+    input_file: Option<String>,
+    stdin: bool,
+    ascii: bool,
+    patches: Vec<(Word, Word)>,
+    max_steps: Option<u64>,
+    trace: bool,
+    dump: bool,
+    coverage: bool,
+    self_modifying: bool,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("intcode: {}", message);
+    process::exit(1);
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str, what: &str) -> T {
+    match text.trim().parse() {
+        Ok(n) => n,
+        Err(_) => fail(&format!("bad {}: {:?}", what, text)),
+    }
+}
+
+// `ADDR=VAL`, where the address can't be negative
+fn parse_patch(patch: &str) -> Result<(Word, Word), String> {
+    let (addr, val) = match patch.find('=') {
+        Some(i) => (&patch[..i], &patch[i + 1..]),
+        None => return Err(format!("patch should look like ADDR=VAL, not {:?}", patch)),
+    };
+    let addr: Word = addr
+        .trim()
+        .parse()
+        .map_err(|_| format!("bad address: {:?}", addr))?;
+    if addr < 0 {
+        return Err(format!("can't patch negative address {}", addr));
+    }
+    let val = val
+        .trim()
+        .parse()
+        .map_err(|_| format!("bad value: {:?}", val))?;
+    Ok((addr, val))
+}
+
+fn parse_args(args: Vec<String>) -> Options {
+    let mut options = Options {
+        program: String::new(),
+        inputs: vec![],
+        input_file: None,
+        stdin: false,
+        ascii: false,
+        patches: vec![],
+        max_steps: None,
+        trace: false,
+        dump: false,
+        coverage: false,
+        self_modifying: false,
+    };
+    let mut program = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| match args.next() {
+            Some(value) => value,
+            None => fail(&format!("{} needs a value", name)),
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "-i" | "--input" => options.inputs.push(value(&arg)),
+            "--input-file" => options.input_file = Some(value(&arg)),
+            "--stdin" => options.stdin = true,
+            "--ascii" => options.ascii = true,
+            "-p" | "--patch" => match parse_patch(&value(&arg)) {
+                Ok(patch) => options.patches.push(patch),
+                Err(message) => fail(&format!("{}\n\n{}", message, USAGE)),
+            },
+            "--max-steps" => options.max_steps = Some(parse_number(&value(&arg), "step count")),
+            "--trace" => options.trace = true,
+            "--dump" => options.dump = true,
+            "--coverage" => options.coverage = true,
+            "--self-modifying" => options.self_modifying = true,
+            _ if program.is_none() && (arg == "-" || !arg.starts_with('-')) => program = Some(arg),
+            _ => fail(&format!("unexpected argument {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+    match program {
+        Some(program) => options.program = program,
+        None => fail(USAGE),
+    }
+    if options.program == "-" && options.stdin {
+        fail("can't read both the program and the inputs from stdin");
+    }
+    options
+}
+
+fn read_inputs(options: &Options) -> Vec<Word> {
+    let mut text = vec![];
+    if let Some(path) = &options.input_file {
+        match fs::read_to_string(path) {
+            Ok(contents) => text.push(contents),
+            Err(error) => fail(&format!("{}: {}", path, error)),
+        }
+    }
+    if options.stdin {
+        let mut contents = String::new();
+        if let Err(error) = io::stdin().read_to_string(&mut contents) {
+            fail(&format!("stdin: {}", error));
+        }
+        text.push(contents);
+    }
+
+    let mut inputs = vec![];
+    for arg in &options.inputs {
+        if options.ascii {
+            inputs.extend(arg.chars().map(|c| c as Word));
+        } else {
+            inputs.extend(arg.split(',').map(|n| parse_number::<Word>(n, "input")));
+        }
+    }
+    for contents in text {
+        if options.ascii {
+            inputs.extend(contents.chars().map(|c| c as Word));
+        } else {
+            inputs.extend(
+                contents
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|n| !n.is_empty())
+                    .map(|n| parse_number::<Word>(n, "input")),
+            );
+        }
+    }
+    inputs
+}
+
+fn load_program(path: &str) -> Memory {
+    let loaded = if path == "-" {
+        Memory::read_from(io::stdin(), Format::Text)
+    } else {
+        Memory::load_file(path)
+    };
+    match loaded {
+        Ok(memory) => memory,
+        Err(error) => fail(&format!("{}: {}", path, error)),
+    }
+}
+
+fn print_output(out: &mut impl Write, value: Word, ascii: bool) {
+    let result = if ascii && (0..128).contains(&value) {
+        write!(out, "{}", value as u8 as char)
+    } else if ascii {
+        // not a character, most likely an answer
+        writeln!(out, "\n{}", value)
+    } else {
+        writeln!(out, "{}", value)
+    };
+    if result.is_err() {
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1).collect());
+    // patched before anything runs, so the coverage report shows what ran
+    let mut program = load_program(&options.program);
+    for (addr, value) in &options.patches {
+        program.write(*addr, *value);
+    }
+    let mut computer = Computer::new(program.clone());
+    if options.coverage {
+        computer.coverage = Some(Coverage::new());
+    }
+    if options.self_modifying {
+        computer.modifications = Some(Modifications::new());
+    }
+    let mut inputs = read_inputs(&options).into_iter();
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut steps = 0;
+    // don't trace an input instruction twice when it waits for input
+    let mut waiting = false;
+    let status = loop {
+        if options.max_steps == Some(steps) {
+            break format!("stopped after {} steps", steps);
+        }
+        if options.trace && !waiting {
+            let text = disassemble_one(&computer.memory, computer.pc())
+                .map_or_else(|| "??".to_string(), |(text, _)| text);
+            eprintln!(
+                "{:>6} rb={:<6} {}",
+                computer.pc(),
+                computer.relative_base(),
+                text
+            );
+        }
+        match computer.step() {
+            Ok(true) => {
+                steps += 1;
+                waiting = false;
+            }
+            Ok(false) if computer.halted() => break String::new(),
+            Ok(false) => match inputs.next() {
+                Some(input) => {
+                    computer.input(input);
+                    waiting = true;
+                }
+                None => break "ran out of input".to_string(),
+            },
+            Err(trap) => break trap.to_string(),
+        }
+        for output in computer.outputs.drain(..) {
+            print_output(&mut out, output, options.ascii);
+        }
+    };
+    drop(out);
+
+    if options.dump {
+        println!("{}", computer.memory);
+    }
+    if let Some(coverage) = &computer.coverage {
+        eprintln!(
+            "{}\n{}",
+            coverage.annotated(&program),
+            coverage.report(&program)
+        );
+    }
+    if let Some(modifications) = &computer.modifications {
+        eprintln!("{}", modifications.report());
+    }
+    if !status.is_empty() {
+        fail(&status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patch() {
+        assert_eq!(Ok((1, 12)), parse_patch("1=12"));
+        assert_eq!(Ok((0, -5)), parse_patch(" 0 = -5"));
+        assert_eq!(
+            Err("can't patch negative address -1".to_string()),
+            parse_patch("-1=5")
+        );
+        assert_eq!(
+            Err("patch should look like ADDR=VAL, not \"7\"".to_string()),
+            parse_patch("7")
+        );
+        assert_eq!(Err("bad value: \"x\"".to_string()), parse_patch("7=x"));
+    }
+}
@@ -0,0 +1,221 @@
+use advent2019::wire::{Board, Metric, Point};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+const USAGE: &str = "usage: wires COMMAND INPUT [options]
+
+INPUT has one wire per line, like R75,D30,U83, or is - for stdin. Moves go
+R, L, U, D or diagonally NE, NW, SE, SW.
+
+commands:
+  closest                the crossing nearest the origin and its distance
+  steps                  the crossing reached in the fewest combined steps
+  crossings              every crossing and the wires that meet there
+  svg                    draw the wires, crossings and both answers
+
+options:
+  --metric NAME          (closest) manhattan, chebyshev, euclidean or steps
+  --from X,Y             (closest) measure from here instead of the origin
+  --at-least K           (crossings) only where at least K wires meet
+  -o, --output PATH      (svg) write the image to PATH instead of stdout";
+
+struct Options {
+    command: String,
+    input: String,
+    metric: Metric,
+    from: Point,
+    at_least: usize,
+    output: Option<String>,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("wires: {}", message);
+    process::exit(1);
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str, what: &str) -> T {
+    match text.trim().parse() {
+        Ok(n) => n,
+        Err(_) => fail(&format!("bad {}: {:?}", what, text)),
+    }
+}
+
+fn parse_point(text: &str) -> Point {
+    match text.split(',').collect::<Vec<&str>>().as_slice() {
+        [x, y] => Point::new(parse_number(x, "x"), parse_number(y, "y")),
+        _ => fail(&format!("bad point {:?}, expected X,Y", text)),
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Options {
+    let mut options = Options {
+        command: String::new(),
+        input: String::new(),
+        metric: Metric::Manhattan,
+        from: Point::ORIGIN,
+        at_least: 2,
+        output: None,
+    };
+    let mut positional = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| match args.next() {
+            Some(value) => value,
+            None => fail(&format!("{} needs a value", name)),
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--metric" => {
+                options.metric = match value(&arg).parse() {
+                    Ok(metric) => metric,
+                    Err(error) => fail(&error.to_string()),
+                }
+            }
+            "--from" => options.from = parse_point(&value(&arg)),
+            "--at-least" => options.at_least = parse_number(&value(&arg), "wire count"),
+            "-o" | "--output" => options.output = Some(value(&arg)),
+            _ if arg == "-" || !arg.starts_with('-') => positional.push(arg),
+            _ => fail(&format!("unexpected argument {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+    match positional.as_slice() {
+        [command, input] => {
+            options.command = command.clone();
+            options.input = input.clone();
+        }
+        _ => fail(USAGE),
+    }
+    options
+}
+
+fn load_board(path: &str) -> Board {
+    let mut text = String::new();
+    let read = if path == "-" {
+        io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        fs::read_to_string(path).map(|contents| text = contents)
+    };
+    if let Err(error) = read {
+        fail(&format!("{}: {}", path, error));
+    }
+    match Board::parse(&text) {
+        Ok(board) => board,
+        Err(error) => fail(&format!("{}: {}", path, error)),
+    }
+}
+
+// what `command` prints, or why it failed
+fn run(board: &Board, options: &Options) -> Result<String, String> {
+    let never = || "the wires never cross".to_string();
+    match options.command.as_str() {
+        "closest" => {
+            let (_, point, distance) = board
+                .closest_crossing_by(&options.metric, options.from)
+                .ok_or_else(never)?;
+            Ok(format!("{},{} distance {}\n", point.x, point.y, distance))
+        }
+        "steps" => {
+            let ((a, b), point, steps) = board.quickest_crossing().ok_or_else(never)?;
+            Ok(format!(
+                "{},{} steps {} (wires {} and {})\n",
+                point.x, point.y, steps, a, b
+            ))
+        }
+        "crossings" => {
+            let mut output = String::new();
+            for crossing in board.crossings_of(options.at_least) {
+                let wires: Vec<String> = crossing.wires.iter().map(usize::to_string).collect();
+                output += &format!(
+                    "{},{} wires {}\n",
+                    crossing.point.x,
+                    crossing.point.y,
+                    wires.join(",")
+                );
+            }
+            Ok(output)
+        }
+        "svg" => match &options.output {
+            Some(path) => match board.save_svg(path) {
+                Ok(()) => Ok(String::new()),
+                Err(error) => Err(format!("{}: {}", path, error)),
+            },
+            None => Ok(board.svg()),
+        },
+        command => Err(format!("unknown command {:?}\n\n{}", command, USAGE)),
+    }
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1).collect());
+    let board = load_board(&options.input);
+    match run(&board, &options) {
+        Ok(output) => print!("{}", output),
+        Err(message) => fail(&message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = "R8,U5,L5,D3\nU7,R6,D4,L4";
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn closest(line: &str) -> Result<String, String> {
+        run(&Board::parse(BOARD).unwrap(), &parse_args(args(line)))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(args("closest - --metric chebyshev --from 3,-4"));
+        assert_eq!(("closest", "-"), (&*options.command, &*options.input));
+        assert!(matches!(options.metric, Metric::Chebyshev));
+        assert_eq!(Point::new(3, -4), options.from);
+        assert_eq!((2, None), (options.at_least, options.output));
+
+        let options = parse_args(args("--at-least 3 crossings wires.txt -o out.svg"));
+        assert_eq!(
+            ("crossings", "wires.txt"),
+            (&*options.command, &*options.input)
+        );
+        assert!(matches!(options.metric, Metric::Manhattan));
+        assert_eq!(Point::ORIGIN, options.from);
+        assert_eq!(
+            (3, Some("out.svg".to_string())),
+            (options.at_least, options.output)
+        );
+    }
+
+    #[test]
+    fn test_closest() {
+        assert_eq!(Ok("3,3 distance 6\n".to_string()), closest("closest -"));
+        assert_eq!(
+            Ok("6,5 distance 1\n".to_string()),
+            closest("closest - --from 6,6")
+        );
+        assert_eq!(
+            Ok("3,3 distance 4.242640687119285\n".to_string()),
+            closest("closest - --metric euclidean")
+        );
+        assert_eq!(
+            Ok("6,5 distance 30\n".to_string()),
+            closest("closest - --metric steps --from 3,3")
+        );
+        let apart = Board::parse("R2\nL2").unwrap();
+        assert_eq!(
+            Err("the wires never cross".to_string()),
+            run(&apart, &parse_args(args("closest -")))
+        );
+        assert!(closest("nearest -")
+            .unwrap_err()
+            .starts_with("unknown command"));
+    }
+}
@@ -98,6 +98,6 @@ mod tests {
 	    step(&mut moons);
 	}
 
-	assert_eq!(179,moons.iter().map(|m| m.total_energy()).sum());
+	assert_eq!(179,moons.iter().map(|m| m.total_energy()).sum::<i32>());
     }
 }
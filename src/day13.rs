@@ -1,5 +1,9 @@
+use super::intcode::{Framebuffer, Word};
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// where the screen is mapped, well past anything the program uses
+const SCREEN: Word = 1 << 32;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum Tile {
@@ -35,14 +39,16 @@ impl Tile {
 
 pub struct Game {
     computer: super::intcode::Computer,
-    display: HashMap<(i64, i64), Tile>,
+    display: Arc<Mutex<Framebuffer<i64>>>,
     score: i64,
 }
 
 impl Game {
     pub fn new(input: &str) -> Game {
         let mut computer = super::intcode::Computer::load(input);
-        let display: HashMap<(i64, i64), Tile> = HashMap::new();
+        let display = Arc::new(Mutex::new(Framebuffer::new(44, 20)));
+        let size = display.lock().unwrap().size();
+        computer.memory.map(SCREEN..SCREEN + size, display.clone());
         computer.run();
         Game {
             computer: computer,
@@ -60,25 +66,44 @@ loop {
     }
 
     fn read_output(&mut self) {
-        for (x, y, tile_id) in self.computer.outputs.iter().tuples() {
-            if (*x, *y) == (-1, 0) {
-                self.score = *tile_id
-            } else {
-                self.display.insert((*x, *y), Tile::new(*tile_id));
+        let (width, height) = {
+            let display = self.display.lock().unwrap();
+            (display.width as Word, display.height as Word)
+        };
+        let outputs = std::mem::take(&mut self.computer.outputs);
+        for (x, y, tile_id) in outputs.into_iter().tuples() {
+            if (x, y) == (-1, 0) {
+                self.score = tile_id
+            } else if (0..width).contains(&x) && (0..height).contains(&y) {
+                // drawn through memory, like the program would itself
+                self.computer.memory.write(SCREEN + y * width + x, tile_id);
             }
+            // tiles off the screen aren't drawn
         }
-        self.computer.outputs = vec![];
     }
 
     pub fn draw(&self) {
         println!("Score: {}", self.score);
-	println!("Blocks: {}", self.display.iter().filter(|(_,tile)| **tile == Tile::Block).count());
+        let display = self.display.lock().unwrap();
+	println!("Blocks: {}", display.cells().iter().filter(|tile_id| Tile::new(**tile_id) == Tile::Block).count());
 
-        for y in 0..20 {
-            for x in 0..44 {
-                print!("{}", self.display[&(x, y)].draw())
-            }
-            println!();
-        }
+        println!("{}", display.render(|tile_id| Tile::new(*tile_id).draw()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_output() {
+        // a block at (1, 2), the score, and a wall off the edge of the screen
+        let mut game = Game::new("104,1,104,2,104,2,104,-1,104,0,104,7,104,50,104,0,104,1,99");
+        game.read_output();
+        assert_eq!(7, game.score);
+        let display = game.display.lock().unwrap();
+        assert_eq!(&2, display.get(1, 2));
+        assert_eq!(1, display.cells().iter().filter(|tile_id| **tile_id != 0).count());
+        assert!(game.computer.outputs.is_empty());
     }
 }
@@ -1,45 +1,50 @@
-fn valid(num: i32) -> bool {
-    let digits: Vec<char> = num.to_string().chars().collect();
-    let mut has_double = false;
-    for i in 0..(digits.len() - 1) {
-        if digits[i] == digits[i + 1] {
-            has_double = true;
-        }
-        if digits[i] > digits[i + 1] {
-            return false;
-        }
-    }
-    has_double
-}
+#[cfg(test)]
+use crate::password::Rule;
+use crate::password::{count_non_decreasing, Run};
+use std::convert::TryFrom;
 
-fn valid2(num: i32) -> bool {
-    let digits: Vec<char> = num.to_string().chars().collect();
-    for i in 0..(digits.len() - 1) {
-        if digits[i] > digits[i + 1] {
-            return false;
-        }
-    }
-    for i in 0..(digits.len() - 1) {
-        let prev = if i == 0 { None } else { digits.get(i - 1) };
-        let this = digits.get(i);
-        let next = digits.get(i + 1);
-        let next2 = digits.get(i + 2);
-        if prev != this && this == next && next != next2 {
-            return true;
-        }
+// counted without trying each number, and no password is negative
+fn count(start: i32, end: i32, run: Run) -> i32 {
+    let start = u64::try_from(start).unwrap_or(0);
+    match u64::try_from(end) {
+        Ok(end) => count_non_decreasing(start..=end, run) as i32,
+        Err(_) => 0,
     }
-    false
 }
 
 pub fn day4(start: i32, end: i32) -> i32 {
-    let mut count = 0;
-    for i in start..(end + 1) {
-        if valid(i) {
-            count += 1;
-        }
-    }
-    count
+    count(start, end, Run::AtLeast(2))
+}
+
+pub fn day4_2(start: i32, end: i32) -> i32 {
+    count(start, end, Run::Exactly(2))
+}
+
+// the same rules checked one number at a time, which the counts are
+// tested against
+
+// digits never decrease and two adjacent ones are the same
+#[cfg(test)]
+fn rules() -> Rule {
+    Rule::NonDecreasing.and(Rule::RunOfAtLeast(2))
+}
+
+// the same, but the pair can't be part of a larger group
+#[cfg(test)]
+fn rules2() -> Rule {
+    Rule::NonDecreasing.and(Rule::RunOfExactly(2))
+}
+
+#[cfg(test)]
+fn valid(num: i32) -> bool {
+    matches!(u64::try_from(num), Ok(num) if rules().check(num))
+}
+
+#[cfg(test)]
+fn valid2(num: i32) -> bool {
+    matches!(u64::try_from(num), Ok(num) if rules2().check(num))
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,4 +63,21 @@ mod tests {
         assert_eq!(false, valid2(123444));
         assert_eq!(true, valid2(111122));
     }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(1, day4(111110, 111111));
+        assert_eq!(0, day4_2(111110, 111111));
+        assert_eq!(1, day4(-100, 11));
+        assert_eq!(0, day4(-100, -1));
+        assert!(!valid(-11));
+        // the same as trying every six-digit number
+        let count = |valid: fn(i32) -> bool| (100000..=999999).filter(|i| valid(*i)).count();
+        assert_eq!(count(valid) as i32, day4(100000, 999999));
+        assert_eq!(count(valid2) as i32, day4_2(100000, 999999));
+        assert_eq!(
+            day4(100000, 200000) as usize,
+            rules().count(100000..=200000)
+        );
+    }
 }
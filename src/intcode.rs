@@ -1,82 +1,207 @@
 use permutohedron::Heap;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "async")]
+mod asynchronous;
+mod assemble;
+mod compile;
+mod conformance;
+mod coverage;
+mod device;
+mod disassemble;
+mod loader;
+mod modifications;
+mod optimize;
+mod profile;
+mod replay;
+mod search;
+#[cfg(feature = "serialize")]
+mod serialize;
+mod value;
+
+#[cfg(feature = "async")]
+pub use asynchronous::AsyncError;
+pub use assemble::{assemble, AssembleError};
+pub use compile::{compile, CompileError};
+pub use conformance::{Backend, Case, Failure, FixtureError, Interpreter, Outcome, Stop, Suite};
+pub use coverage::Coverage;
+pub use device::{Device, Framebuffer, Keyboard, SharedDevice, Timer};
+pub use disassemble::{disassemble, disassemble_one};
+pub use loader::{Format, LoadError};
+pub use modifications::{Modifications, Site};
+pub use optimize::{Optimizer, Rewrite};
+pub use profile::{Isa, Profile, Uninitialized};
+pub use replay::{Divergence, Event, Recorder, ReplayError, Session};
+pub use search::{Candidate, Search};
+#[cfg(feature = "serialize")]
+pub use serialize::{SerializeError, TraceStep};
+pub use value::{Overflow, Value};
 
 pub type Word = i64;
 
-pub struct Computer {
-    input: Option<Word>,
+#[cfg_attr(
+    feature = "serialize",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "W: Serialize + Clone",
+        deserialize = "W: Deserialize<'de>"
+    ))
+)]
+pub struct Computer<W = Word> {
+    input: Option<W>,
     pc: Word,
-    pub memory: Memory,
-    pub outputs: Vec<Word>,
+    pub memory: Memory<W>,
+    pub outputs: Vec<W>,
+    pub overflow: Overflow,
+    pub profile: Profile,
+    pub coverage: Option<Coverage>,
+    pub modifications: Option<Modifications>,
     halted: bool,
     relative_base: Word,
 }
 
-#[derive(Debug)]
-pub struct Memory {
-    mem: HashMap<Word, Word>,
+/// Why the machine stopped executing other than by halting or waiting for input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Trap {
+    Overflow { pc: Word },
+    BadAddress { pc: Word },
+    IllegalInstruction { pc: Word, instruction: Word },
+    UninitializedRead { pc: Word, addr: Word },
+}
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Trap::Overflow { pc } => write!(f, "arithmetic overflow at {}", pc),
+            Trap::BadAddress { pc } => write!(f, "value at {} is not a valid address", pc),
+            Trap::IllegalInstruction { pc, instruction } => {
+                write!(f, "illegal instruction {} at {}", instruction, pc)
+            }
+            Trap::UninitializedRead { pc, addr } => {
+                write!(f, "read of uninitialized address {} at {}", addr, pc)
+            }
+        }
+    }
+}
+
+/// Serializing a memory with devices mapped fails, since they can't be saved.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(Deserialize),
+    serde(
+        from = "serialize::Image<W>",
+        bound(deserialize = "W: Deserialize<'de>")
+    )
+)]
+pub struct Memory<W = Word> {
+    mem: HashMap<Word, W>,
+    devices: Vec<device::Mapping<W>>,
 }
 
 impl Memory {
     pub fn load(text: &str) -> Memory {
-        let mut mem = HashMap::new();
-        for (i, word) in text.split(",").enumerate() {
-            let word = word.parse::<Word>().unwrap();
-            mem.insert(i as Word, word);
+        Memory::parse(text)
+    }
+}
+
+impl<W: Value> Memory<W> {
+    pub fn empty() -> Memory<W> {
+        Memory {
+            mem: HashMap::new(),
+            devices: vec![],
         }
-        Self { mem: mem }
     }
 
-    pub fn read(&mut self, ptr: Word) -> Word {
+    pub fn parse(text: &str) -> Memory<W> {
+        match Memory::try_parse(text) {
+            Ok(memory) => memory,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn read(&mut self, ptr: Word) -> W {
         if ptr < 0 {
             panic!("out of bounds");
         }
-        match self.mem.get(&ptr) {
-            Some(val) => *val,
-            None => {
-                self.mem.insert(ptr, 0);
-                0
-            }
+        if let Some((device, offset)) = self.device_at(ptr) {
+            return device.lock().unwrap().read(offset);
         }
+        self.mem.entry(ptr).or_insert_with(W::zero).clone()
     }
 
-    pub fn write(&mut self, ptr: Word, value: Word) {
+    /// The value stored at `ptr`, without going through devices or marking
+    /// the cell as initialized.
+    pub fn peek(&self, ptr: Word) -> W {
+        self.mem.get(&ptr).cloned().unwrap_or_else(W::zero)
+    }
+
+    pub fn initialized(&self, ptr: Word) -> bool {
+        self.mem.contains_key(&ptr) || self.device_at(ptr).is_some()
+    }
+
+    pub fn write(&mut self, ptr: Word, value: W) {
         if ptr < 0 {
             panic!("out of bounds");
         }
+        if let Some((device, offset)) = self.device_at(ptr) {
+            device.lock().unwrap().write(offset, value);
+            return;
+        }
         self.mem.insert(ptr, value);
     }
 
+    /// One past the highest address that has ever been touched.
+    pub fn len(&self) -> Word {
+        self.mem.keys().max().map_or(0, |max| max + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mem.is_empty()
+    }
+
+    #[cfg(test)]
     fn print(&self) -> String {
-        let mut keys: Vec<&Word> = self.mem.keys().collect();
-        keys.sort();
+        self.to_string()
+    }
+}
 
-        keys.iter()
-            .map(|i| self.mem[*i].to_string())
-            .collect::<Vec<String>>()
-            .join(",")
+/// Every cell from 0 to `len()`, comma separated, with gaps shown as 0.
+impl<W: Value> fmt::Display for Memory<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let zero = W::zero();
+        let words: Vec<String> = (0..self.len())
+            .map(|i| self.mem.get(&i).unwrap_or(&zero).to_string())
+            .collect();
+        write!(f, "{}", words.join(","))
     }
 }
 
-#[derive(Debug)]
-enum Mode {
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Mode {
     Position,
     Immediate,
     Relative,
 }
 
 impl Mode {
-    fn new(num: Word) -> Self {
+    fn new(num: Word) -> Option<Self> {
         match num {
-            0 => Self::Position,
-            1 => Self::Immediate,
-            2 => Self::Relative,
-            _ => panic!("unknown mode"),
+            0 => Some(Self::Position),
+            1 => Some(Self::Immediate),
+            2 => Some(Self::Relative),
+            _ => None,
         }
     }
 }
-#[derive(Debug)]
-enum Opcode {
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Opcode {
     Add(Mode, Mode, Mode),
     Mult(Mode, Mode, Mode),
     Input(Mode),
@@ -89,12 +214,12 @@ enum Opcode {
     Halt,
 }
 impl Opcode {
-    fn new(opcode: Word) -> Self {
+    fn new(opcode: Word) -> Option<Self> {
         let op = opcode % 100;
-        let mode1 = Mode::new((opcode / 100) % 10);
-        let mode2 = Mode::new((opcode / 1000) % 10);
-        let mode3 = Mode::new((opcode / 10000) % 10);
-        match op {
+        let mode1 = Mode::new((opcode / 100) % 10)?;
+        let mode2 = Mode::new((opcode / 1000) % 10)?;
+        let mode3 = Mode::new((opcode / 10000) % 10)?;
+        let opcode = match op {
             1 => Opcode::Add(mode1, mode2, mode3),
             2 => Opcode::Mult(mode1, mode2, mode3),
             3 => Opcode::Input(mode1),
@@ -105,189 +230,330 @@ impl Opcode {
             8 => Opcode::Eq(mode1, mode2, mode3),
             9 => Opcode::ARB(mode1), //adjust relative base
             99 => Opcode::Halt,
-            x => panic!("unknown opcode: {}", x),
+            _ => return None,
+        };
+        Some(opcode)
+    }
+
+    fn modes(&self) -> Vec<&Mode> {
+        match self {
+            Opcode::Add(mode1, mode2, mode3)
+            | Opcode::Mult(mode1, mode2, mode3)
+            | Opcode::LT(mode1, mode2, mode3)
+            | Opcode::Eq(mode1, mode2, mode3) => vec![mode1, mode2, mode3],
+            Opcode::JIT(mode1, mode2) | Opcode::JIF(mode1, mode2) => vec![mode1, mode2],
+            Opcode::Input(mode1) | Opcode::Output(mode1) | Opcode::ARB(mode1) => vec![mode1],
+            Opcode::Halt => vec![],
         }
     }
 }
 
 impl Computer {
     pub fn load(text: &str) -> Computer {
-        let mut memory = Memory::load(text);
+        Computer::new(Memory::load(text))
+    }
+}
+
+impl<W: Value> Computer<W> {
+    pub fn new(memory: Memory<W>) -> Computer<W> {
         Computer {
             input: None,
             pc: 0,
-            memory: memory,
+            memory,
             outputs: vec![],
+            overflow: Overflow::Wrap,
+            profile: Profile::default(),
+            coverage: None,
+            modifications: None,
             halted: false,
             relative_base: 0,
         }
     }
 
-    pub fn input(&mut self, input: Word) {
+    pub fn input(&mut self, input: W) {
         match self.input {
             None => self.input = Some(input),
             Some(_) => panic!("already have input!"),
         }
     }
 
-    pub fn run_with_input(&mut self, input: Word) {
+    pub fn run_with_input(&mut self, input: W) {
         self.input(input);
         self.run();
     }
 
     pub fn run(&mut self) {
-	let mut r = self.step();
-	while r {
-	    r = self.step();
-	}
+        if let Err(trap) = self.try_run() {
+            panic!("{}", trap);
+        }
+    }
+
+    /// Like `run`, but returns a trap instead of panicking on it. The pc is
+    /// left on the instruction that trapped.
+    pub fn try_run(&mut self) -> Result<(), Trap> {
+        while self.step()? {}
+        Ok(())
     }
 
     pub fn halted(&self) -> bool {
-	self.halted
+        self.halted
+    }
+
+    pub fn pc(&self) -> Word {
+        self.pc
+    }
+
+    pub fn relative_base(&self) -> Word {
+        self.relative_base
     }
 
-    fn step(&mut self) -> bool{
-        let opcode = Opcode::new(self.read_and_advance());
+    /// Moves the pc, e.g. to run code written somewhere else. A halted
+    /// machine is resumed.
+    pub fn set_pc(&mut self, pc: Word) {
+        self.pc = pc;
+        self.halted = false;
+    }
+
+    pub fn set_relative_base(&mut self, relative_base: Word) {
+        self.relative_base = relative_base;
+    }
+
+    /// The instruction at the pc, if it decodes.
+    pub fn instruction(&self) -> Option<Opcode> {
+        self.memory.peek(self.pc).to_i64().and_then(Opcode::new)
+    }
+
+    /// Executes one instruction. Returns false if the machine halted or is
+    /// waiting for input instead.
+    pub fn step(&mut self) -> Result<bool, Trap> {
+        let start = self.pc;
+        // measured up front, the instruction might overwrite itself
+        let len = match self.modifications {
+            Some(_) => self.instruction_len(start),
+            None => 0,
+        };
+        let result = self.execute();
+        if let Ok(true) = result {
+            self.memory.tick();
+        }
+        let executed = result == Ok(true) || self.halted;
+        if let (true, Some(coverage)) = (executed, &mut self.coverage) {
+            coverage.executed.insert(start);
+        }
+        if let (true, Some(modifications)) = (executed, &mut self.modifications) {
+            modifications.executed(start, len);
+        }
+        if result.is_err() {
+            self.pc = start;
+        }
+        result
+    }
+
+    fn execute(&mut self) -> Result<bool, Trap> {
+        let pc = self.pc;
+        let instruction = self.read_and_advance(pc)?;
+        let instruction = Self::address(&instruction, pc)?;
+        let opcode = match Opcode::new(instruction) {
+            Some(opcode) if self.profile.isa.accepts(&opcode) => opcode,
+            _ => return Err(Trap::IllegalInstruction { pc, instruction }),
+        };
         match opcode {
             Opcode::Add(mode1, mode2, mode3) => {
-                let inputs = self.get_operands(vec![mode1, mode2]);
-                let output_addr = self.read_and_advance();
+                let inputs = self.get_operands(vec![mode1, mode2], pc)?;
+                let output_addr = self.read_address(pc)?;
 
-                let result = inputs[0] + inputs[1];
-                self.write(output_addr, result, mode3);
+                let result = inputs[0]
+                    .add_with(&inputs[1], self.overflow)
+                    .ok_or(Trap::Overflow { pc })?;
+                self.write(output_addr, result, mode3, pc)?;
             }
 
             Opcode::Mult(mode1, mode2, mode3) => {
-                let inputs = self.get_operands(vec![mode1, mode2]);
-                let output_addr = self.read_and_advance();
+                let inputs = self.get_operands(vec![mode1, mode2], pc)?;
+                let output_addr = self.read_address(pc)?;
 
-                let result = inputs[0] * inputs[1];
-                self.write(output_addr, result, mode3);
+                let result = inputs[0]
+                    .mul_with(&inputs[1], self.overflow)
+                    .ok_or(Trap::Overflow { pc })?;
+                self.write(output_addr, result, mode3, pc)?;
             }
 
             Opcode::Input(mode1) => {
-                match self.input {
-                    Some(input) => {
-                        let output_addr = self.read_and_advance();
-                        self.write(output_addr, input, mode1);
-                        self.input = None;
-                    }
-                    None => {
-                        // move pc back and wait for more input
-                        self.pc -= 1;
-			return false;
-                    }
+                if self.input.is_none() {
+                    // move pc back and wait for more input
+                    self.pc -= 1;
+                    return Ok(false);
                 }
+                let output_addr = self.read_address(pc)?;
+                // keep the input if the write traps
+                let input = self.input.clone().unwrap();
+                self.write(output_addr, input, mode1, pc)?;
+                self.input = None;
             }
 
             Opcode::Output(mode1) => {
-                let inputs = self.get_operands(vec![mode1]);
-                let result = inputs[0];
-                self.outputs.push(result);
+                let mut inputs = self.get_operands(vec![mode1], pc)?;
+                self.outputs.push(inputs.remove(0));
             }
 
             Opcode::JIT(mode1, mode2) => {
-                let inputs = self.get_operands(vec![mode1, mode2]);
-                if inputs[0] != 0 {
-                    self.pc = inputs[1];
+                let inputs = self.get_operands(vec![mode1, mode2], pc)?;
+                if !inputs[0].is_zero() {
+                    self.pc = Self::address(&inputs[1], pc)?;
                 }
             }
 
             Opcode::JIF(mode1, mode2) => {
-                let inputs = self.get_operands(vec![mode1, mode2]);
-                if inputs[0] == 0 {
-                    self.pc = inputs[1];
+                let inputs = self.get_operands(vec![mode1, mode2], pc)?;
+                if inputs[0].is_zero() {
+                    self.pc = Self::address(&inputs[1], pc)?;
                 }
             }
 
             Opcode::LT(mode1, mode2, mode3) => {
-                let inputs = self.get_operands(vec![mode1, mode2]);
-                let output_addr = self.read_and_advance();
+                let inputs = self.get_operands(vec![mode1, mode2], pc)?;
+                let output_addr = self.read_address(pc)?;
 
                 if inputs[0] < inputs[1] {
-                    self.write(output_addr, 1, mode3);
+                    self.write(output_addr, W::one(), mode3, pc)?;
                 } else {
-                    self.write(output_addr, 0, mode3);
+                    self.write(output_addr, W::zero(), mode3, pc)?;
                 }
             }
 
             Opcode::Eq(mode1, mode2, mode3) => {
-                let inputs = self.get_operands(vec![mode1, mode2]);
-                let output_addr = self.read_and_advance();
+                let inputs = self.get_operands(vec![mode1, mode2], pc)?;
+                let output_addr = self.read_address(pc)?;
 
                 if inputs[0] == inputs[1] {
-                    self.write(output_addr, 1, mode3);
+                    self.write(output_addr, W::one(), mode3, pc)?;
                 } else {
-                    self.write(output_addr, 0, mode3);
+                    self.write(output_addr, W::zero(), mode3, pc)?;
                 }
             }
             // adjust relative base
             Opcode::ARB(mode1) => {
-                let inputs = self.get_operands(vec![mode1]);
-                self.relative_base += inputs[0];
+                let inputs = self.get_operands(vec![mode1], pc)?;
+                let offset = Self::address(&inputs[0], pc)?;
+                self.relative_base = self.relative(offset, pc)?;
             }
 
             Opcode::Halt => {
                 self.halted = true;
-		return false;
+                return Ok(false);
             }
         }
-	true
+        Ok(true)
+    }
+
+    // pointers and jump targets have to fit in a `Word` no matter how wide W is
+    fn address(value: &W, pc: Word) -> Result<Word, Trap> {
+        value.to_i64().ok_or(Trap::BadAddress { pc })
+    }
+
+    // `offset` from the relative base, which has to fit in a `Word` too
+    fn relative(&self, offset: Word, pc: Word) -> Result<Word, Trap> {
+        self.relative_base
+            .checked_add(offset)
+            .ok_or(Trap::BadAddress { pc })
+    }
+
+    fn read(&mut self, addr: Word, pc: Word) -> Result<W, Trap> {
+        if addr < 0 {
+            return Err(Trap::BadAddress { pc });
+        }
+        if self.profile.uninitialized == Uninitialized::Trap && !self.memory.initialized(addr) {
+            return Err(Trap::UninitializedRead { pc, addr });
+        }
+        Ok(self.memory.read(addr))
     }
 
-    fn read_and_advance(&mut self) -> Word {
-        let out = self.memory.read(self.pc);
+    fn read_and_advance(&mut self, pc: Word) -> Result<W, Trap> {
+        let out = self.read(self.pc, pc)?;
         self.pc += 1;
-        out
+        Ok(out)
     }
 
-    fn write(&mut self, addr: Word, value: Word, mode: Mode) {
-        match mode {
-            Mode::Position => self.memory.write(addr, value),
-            Mode::Immediate => panic!("can't write in immediate mode"),
-            Mode::Relative => self.memory.write(self.relative_base + addr, value),
+    fn read_address(&mut self, pc: Word) -> Result<Word, Trap> {
+        let value = self.read_and_advance(pc)?;
+        Self::address(&value, pc)
+    }
+
+    fn instruction_len(&self, addr: Word) -> Word {
+        let opcode = self.memory.peek(addr).to_i64().and_then(Opcode::new);
+        opcode.map_or(1, |opcode| 1 + opcode.modes().len() as Word)
+    }
+
+    fn write(&mut self, addr: Word, value: W, mode: Mode, pc: Word) -> Result<(), Trap> {
+        let addr = match mode {
+            Mode::Position => addr,
+            Mode::Immediate => {
+                let instruction = Self::address(&self.memory.peek(pc), pc)?;
+                return Err(Trap::IllegalInstruction { pc, instruction });
+            }
+            Mode::Relative => self.relative(addr, pc)?,
+        };
+        if addr < 0 {
+            return Err(Trap::BadAddress { pc });
         }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.written.insert(addr);
+        }
+        if let Some(modifications) = &mut self.modifications {
+            modifications.written(pc, addr);
+        }
+        self.memory.write(addr, value);
+        Ok(())
     }
 
-    fn get_operands(&mut self, modes: Vec<Mode>) -> Vec<Word> {
+    fn read_data(&mut self, addr: Word, pc: Word) -> Result<W, Trap> {
+        let value = self.read(addr, pc)?;
+        if let Some(coverage) = &mut self.coverage {
+            coverage.read.insert(addr);
+        }
+        Ok(value)
+    }
+
+    fn get_operands(&mut self, modes: Vec<Mode>, pc: Word) -> Result<Vec<W>, Trap> {
         let mut output = vec![];
         for mode in modes {
             let value = match mode {
                 Mode::Position => {
-                    let pointer = self.read_and_advance();
-                    self.memory.read(pointer)
+                    let pointer = self.read_address(pc)?;
+                    self.read_data(pointer, pc)?
                 }
-                Mode::Immediate => self.read_and_advance(),
+                Mode::Immediate => self.read_and_advance(pc)?,
                 Mode::Relative => {
-                    let pointer = self.relative_base + self.read_and_advance();
-                    self.memory.read(pointer)
+                    let offset = self.read_address(pc)?;
+                    let pointer = self.relative(offset, pc)?;
+                    self.read_data(pointer, pc)?
                 }
             };
             output.push(value);
         }
-        output
+        Ok(output)
     }
 }
 
+// every order of `phases`
+fn permutations(mut phases: Vec<Word>) -> Vec<Vec<Word>> {
+    Heap::new(&mut phases).collect()
+}
+
 pub fn day7(input: &str) -> Word {
-    let mut phase_settings = vec![0, 1, 2, 3, 4];
-    let heap = Heap::new(&mut phase_settings);
-    let mut max_output = 0;
-    for permutation in heap {
-        let mut last_output = 0;
-        for i in permutation {
-            let mut computer = Computer::load(input);
-            computer.run_with_input(i);
-            if !computer.halted {
-                computer.run_with_input(last_output);
-            }
-            last_output = computer.outputs[0]
-        }
-        if last_output > max_output {
-            max_output = last_output;
+    let program = Memory::load(input);
+    let search = Search::default();
+    // each amplifier gets its phase, then the signal from the one before
+    let signals = search.map(&permutations(vec![0, 1, 2, 3, 4]), |phases| {
+        let mut signal = 0;
+        for phase in phases {
+            let computer =
+                Candidate::inputs(vec![*phase, signal]).run(&program, search.max_steps)?;
+            signal = *computer.outputs.first()?;
         }
-    }
-    max_output
+        Some(signal)
+    });
+    signals.into_iter().flatten().max().unwrap_or(0)
 }
 
 fn prev_index(i: usize, max: usize) -> usize {
@@ -298,49 +564,48 @@ fn prev_index(i: usize, max: usize) -> usize {
     }
 }
 
-pub fn day7_2(input: &str) -> Word {
-    let mut phase_settings = vec![5, 6, 7, 8, 9];
-    let heap = Heap::new(&mut phase_settings);
-    let mut max_output = 0;
-    for permutation in heap {
-        let mut computers = vec![];
-        for i in permutation {
-            let mut computer = Computer::load(input);
-            computer.run_with_input(i);
-            computers.push(computer);
-        }
-
-        let mut num_halted = 0;
-        let mut i = 1;
-        computers[0].run_with_input(0);
-        while num_halted < computers.len() {
-            i = i % computers.len();
-            if computers[i].halted {
-                num_halted += 1; // this may be a bug
-            } else {
-                let output = *computers[prev_index(i, computers.len())]
-                    .outputs
-                    .last()
-                    .unwrap();
-                computers[i].run_with_input(output);
-            }
+// the last amplifier's final signal, with its output fed back to the first
+fn feedback_loop(input: &str, permutation: &[Word]) -> Word {
+    let mut computers = vec![];
+    for i in permutation {
+        let mut computer = Computer::load(input);
+        computer.run_with_input(*i);
+        computers.push(computer);
+    }
 
-            i += 1;
+    let mut num_halted = 0;
+    let mut i = 1;
+    computers[0].run_with_input(0);
+    while num_halted < computers.len() {
+        i %= computers.len();
+        if computers[i].halted {
+            num_halted += 1; // this may be a bug
+        } else {
+            let output = *computers[prev_index(i, computers.len())]
+                .outputs
+                .last()
+                .unwrap();
+            computers[i].run_with_input(output);
         }
 
-        let last_output = *computers[computers.len() - 1].outputs.last().unwrap();
-
-        if last_output > max_output {
-            max_output = last_output;
-        }
+        i += 1;
     }
-    max_output
+
+    *computers[computers.len() - 1].outputs.last().unwrap()
+}
+
+pub fn day7_2(input: &str) -> Word {
+    let signals = Search::default().map(&permutations(vec![5, 6, 7, 8, 9]), |phases| {
+        feedback_loop(input, phases)
+    });
+    signals.into_iter().max().unwrap_or(0)
 }
 
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use num::BigInt;
 
     #[test]
     fn test_inputer() {
@@ -413,4 +678,98 @@ mod tests {
         computer.run();
         assert_eq!(vec![1125899906842624], computer.outputs);
     }
+
+    #[test]
+    fn test_word_types() {
+        let input = "1102,34915192,34915192,7,4,7,99,0";
+        let mut computer = Computer::new(Memory::<i128>::parse(input));
+        computer.run();
+        assert_eq!(vec![1219070632396864], computer.outputs);
+
+        let mut computer = Computer::new(Memory::<BigInt>::parse(
+            "1102,4294967296,4294967296,7,4,7,99,0",
+        ));
+        computer.overflow = Overflow::Trap;
+        computer.run();
+        assert_eq!("18446744073709551616", computer.outputs[0].to_string());
+
+        let mut computer = Computer::new(Memory::<i32>::parse(input));
+        computer.overflow = Overflow::Trap;
+        assert_eq!(Err(Trap::Overflow { pc: 0 }), computer.try_run());
+        assert!(!computer.halted());
+
+        let mut computer = Computer::new(Memory::<i32>::parse(input));
+        computer.overflow = Overflow::Saturate;
+        computer.run();
+        assert_eq!(vec![i32::MAX], computer.outputs);
+
+        let mut computer = Computer::new(Memory::<i32>::parse(input));
+        computer.run();
+        assert_eq!(vec![34915192i32.wrapping_mul(34915192)], computer.outputs);
+    }
+
+    #[test]
+    fn test_bad_address() {
+        let mut computer = Computer::new(Memory::<BigInt>::parse("1105,1,18446744073709551616,99"));
+        assert_eq!(Err(Trap::BadAddress { pc: 0 }), computer.try_run());
+
+        // relative addresses and the relative base past the end of a Word
+        for program in &[
+            "109,9223372036854775807,204,1,99",
+            "109,9223372036854775807,21101,1,2,1,99",
+            "109,9223372036854775807,109,1,99",
+            "109,-9223372036854775808,109,-1,99",
+        ] {
+            let mut computer = Computer::load(program);
+            assert_eq!(
+                Err(Trap::BadAddress { pc: 2 }),
+                computer.try_run(),
+                "{}",
+                program
+            );
+            assert_eq!(2, computer.pc());
+        }
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut computer = Computer::load("1,0,0,0,99");
+        computer.profile = Profile::strict(Isa::Day2);
+        assert_eq!(Ok(()), computer.try_run());
+        assert_eq!("2,0,0,0,99", computer.memory.print());
+
+        let mut computer = Computer::load("1002,4,3,4,33");
+        computer.profile = Profile::strict(Isa::Day2);
+        assert_eq!(
+            Err(Trap::IllegalInstruction {
+                pc: 0,
+                instruction: 1002
+            }),
+            computer.try_run()
+        );
+        computer.profile = Profile::strict(Isa::Day5);
+        assert_eq!(Ok(()), computer.try_run());
+
+        // day 2 didn't say anything about memory past the end of the program
+        let mut computer = Computer::load("1,0,0,0");
+        computer.profile = Profile::strict(Isa::Day2);
+        assert_eq!(
+            Err(Trap::UninitializedRead { pc: 4, addr: 4 }),
+            computer.try_run()
+        );
+
+        let input = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let mut computer = Computer::load(input);
+        computer.profile = Profile::strict(Isa::Day5);
+        assert_eq!(
+            Err(Trap::IllegalInstruction {
+                pc: 0,
+                instruction: 109
+            }),
+            computer.try_run()
+        );
+        computer.profile = Profile::strict(Isa::Day9);
+        assert_eq!(Ok(()), computer.try_run());
+        assert_eq!(16, computer.outputs.len());
+    }
 }
@@ -0,0 +1,151 @@
+use super::Word;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// opcode, number of operands and which one (if any) is written to
+fn mnemonic(name: &str) -> Option<(Word, usize, Option<usize>)> {
+    Some(match name {
+        "add" => (1, 3, Some(2)),
+        "mul" => (2, 3, Some(2)),
+        "in" => (3, 1, Some(0)),
+        "out" => (4, 1, None),
+        "jt" => (5, 2, None),
+        "jf" => (6, 2, None),
+        "lt" => (7, 3, Some(2)),
+        "eq" => (8, 3, Some(2)),
+        "arb" => (9, 1, None),
+        "hlt" => (99, 0, None),
+        _ => return None,
+    })
+}
+
+fn number(text: &str) -> Result<Word, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("bad number {:?}", text.trim()))
+}
+
+// returns the mode digit and the value
+fn operand(text: &str) -> Result<(Word, Word), String> {
+    let text = text.trim();
+    let inner = match text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        Some(inner) => inner.trim(),
+        None => return Ok((1, number(text)?)),
+    };
+    match inner.strip_prefix("rb") {
+        Some("") => Ok((2, 0)),
+        Some(offset) if offset.starts_with('+') => Ok((2, number(&offset[1..])?)),
+        Some(offset) if offset.starts_with('-') => Ok((2, number(offset)?)),
+        Some(_) => Err(format!("bad operand {:?}", text)),
+        None => Ok((0, number(inner)?)),
+    }
+}
+
+fn instruction(text: &str) -> Result<Vec<Word>, String> {
+    let (name, rest) = match text.find(char::is_whitespace) {
+        Some(i) => (&text[..i], text[i..].trim()),
+        None => (text, ""),
+    };
+    let operands: Vec<&str> = if rest.is_empty() {
+        vec![]
+    } else {
+        rest.split(',').collect()
+    };
+    if name == "data" {
+        return operands.into_iter().map(number).collect();
+    }
+    let (opcode, count, written) =
+        mnemonic(name).ok_or_else(|| format!("unknown instruction {:?}", name))?;
+    if operands.len() != count {
+        return Err(format!(
+            "{} takes {} operands, not {}",
+            name,
+            count,
+            operands.len()
+        ));
+    }
+    let mut words = vec![opcode];
+    let mut scale = 100;
+    for (i, text) in operands.into_iter().enumerate() {
+        let (mode, value) = operand(text)?;
+        if mode == 1 && written == Some(i) {
+            return Err(format!("can't write to an immediate: {:?}", text.trim()));
+        }
+        words[0] += mode * scale;
+        scale *= 10;
+        words.push(value);
+    }
+    Ok(words)
+}
+
+/// Assembles text in the syntax `disassemble` produces back into words.
+/// Instructions are separated by newlines or `;`, `data N, N, ...` emits
+/// raw words and `#` starts a comment.
+pub fn assemble(text: &str) -> Result<Vec<Word>, AssembleError> {
+    let mut words = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        for part in line.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let assembled = instruction(part).map_err(|message| AssembleError {
+                line: i + 1,
+                message,
+            })?;
+            words.extend(assembled);
+        }
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{disassemble, Memory};
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_assemble() {
+        assert_eq!(
+            Ok(vec![1002, 4, 3, 4, 33, 109, -1, 204, 1, 99]),
+            assemble("mul [4], 3, [4]; data 33\narb -1 # comment\nout [rb+1]\n\nhlt")
+        );
+        assert_eq!(
+            Ok(vec![21101, 1, 2, -3, 203, 0]),
+            assemble("add 1,2,[rb-3]; in [rb]")
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text| assemble(text).unwrap_err().to_string();
+        assert_eq!("line 2: unknown instruction \"nop\"", error("hlt\nnop"));
+        assert_eq!("line 1: jt takes 2 operands, not 1", error("jt 1"));
+        assert_eq!("line 1: can't write to an immediate: \"5\"", error("in 5"));
+        assert_eq!("line 1: bad operand \"[rb*1]\"", error("out [rb*1]"));
+        assert_eq!("line 1: bad number \"x\"", error("out [x]"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for day in &["day2", "day11", "day13"] {
+            let text = fs::read_to_string(format!("data/{}.txt", day)).unwrap();
+            let memory: Memory = Memory::load(&text);
+            let listing: Vec<String> = disassemble(&memory, 0, memory.len())
+                .into_iter()
+                .map(|(_, line)| line)
+                .collect();
+            let words: Vec<Word> = (0..memory.len()).map(|addr| memory.peek(addr)).collect();
+            assert_eq!(Ok(words), assemble(&listing.join("\n")), "{}", day);
+        }
+    }
+}
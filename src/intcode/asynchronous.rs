@@ -0,0 +1,179 @@
+use super::{Computer, Trap, Value};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// How many instructions a machine runs before giving other tasks on the
+/// executor a turn.
+const STEPS_PER_YIELD: u32 = 1000;
+
+#[derive(Debug)]
+pub enum AsyncError<E> {
+    Trap(Trap),
+    /// The machine wanted input but the input stream had ended.
+    InputClosed,
+    Output(E),
+}
+
+impl<E: fmt::Display> fmt::Display for AsyncError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsyncError::Trap(trap) => write!(f, "{}", trap),
+            AsyncError::InputClosed => write!(f, "input closed while waiting for input"),
+            AsyncError::Output(error) => write!(f, "couldn't send output: {}", error),
+        }
+    }
+}
+
+// Returns Pending once, so a busy machine doesn't hog the executor.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+impl<W: Value> Computer<W> {
+    /// Runs until the machine halts, awaiting `inputs` whenever it blocks on
+    /// an input instruction and sending each output to `outputs` as soon as
+    /// it's produced.
+    pub async fn run_async<I, O>(
+        &mut self,
+        mut inputs: I,
+        mut outputs: O,
+    ) -> Result<(), AsyncError<O::Error>>
+    where
+        I: Stream<Item = W> + Unpin,
+        O: Sink<W> + Unpin,
+    {
+        let mut since_yield = 0;
+        loop {
+            let progressed = self.step().map_err(AsyncError::Trap)?;
+            for output in self.outputs.drain(..).collect::<Vec<W>>() {
+                outputs.send(output).await.map_err(AsyncError::Output)?;
+            }
+            if progressed {
+                since_yield += 1;
+                if since_yield == STEPS_PER_YIELD {
+                    since_yield = 0;
+                    YieldNow(false).await;
+                }
+            } else if self.halted() {
+                return Ok(());
+            } else {
+                // step rewound the pc onto the input instruction, so it runs
+                // again once we have something to give it
+                match inputs.next().await {
+                    Some(input) => self.input(input),
+                    None => return Err(AsyncError::InputClosed),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+    use futures::executor::block_on;
+    use futures::future::join_all;
+    use futures::stream;
+
+    // day 7 part 2 with every amplifier a task on the same thread, and
+    // channels between them instead of day7_2's round robin
+    fn amplify(program: &str, phases: &[i64]) -> i64 {
+        let (senders, mut receivers): (Vec<_>, Vec<_>) =
+            phases.iter().map(|_| mpsc::unbounded()).unzip();
+        for (sender, phase) in senders.iter().zip(phases) {
+            sender.unbounded_send(*phase).unwrap();
+        }
+        senders[0].unbounded_send(0).unwrap();
+
+        let mut computers: Vec<Computer> = phases.iter().map(|_| Computer::load(program)).collect();
+        // each amplifier feeds the next, and the last one feeds the first
+        let outputs = senders.iter().cycle().skip(1).cloned();
+        let tasks = computers
+            .iter_mut()
+            .zip(receivers.iter_mut())
+            .zip(outputs)
+            .map(|((computer, input), output)| computer.run_async(input, output));
+        for result in block_on(join_all(tasks)) {
+            result.unwrap();
+        }
+
+        // the last amplifier's final signal arrives after the first has halted
+        let mut signal = 0;
+        while let Ok(value) = receivers[0].try_recv() {
+            signal = value;
+        }
+        signal
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        assert_eq!(
+            139629729,
+            amplify(
+                "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+                &[9, 8, 7, 6, 5]
+            )
+        );
+        assert_eq!(18216, amplify("3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10", &[9, 7, 8, 5, 6]));
+    }
+
+    #[test]
+    fn test_streams() {
+        // echoes inputs until it reads a 0
+        let program = "3,20,4,20,1005,20,0,99";
+        let (sender, receiver) = mpsc::unbounded();
+        let mut computer = Computer::load(program);
+        block_on(computer.run_async(stream::iter(vec![5, 6, 0]), sender)).unwrap();
+        assert_eq!(vec![5, 6, 0], block_on(receiver.collect::<Vec<i64>>()));
+
+        let (sender, _receiver) = mpsc::unbounded();
+        let mut computer = Computer::load(program);
+        match block_on(computer.run_async(stream::iter(vec![5]), sender)) {
+            Err(AsyncError::InputClosed) => (),
+            _ => panic!("expected the input to run out"),
+        }
+    }
+
+    #[test]
+    fn test_yields_while_busy() {
+        // counts to 5000 before outputting, so it has to yield along the way
+        let program = "1001,20,1,20,1007,20,5000,21,1005,21,0,4,20,99";
+        let (sender, mut receiver) = mpsc::unbounded();
+        let mut computer = Computer::load(program);
+        let machine = computer.run_async(stream::empty(), sender);
+        // another task on the same thread, which only gets turns while the
+        // machine is counting if the machine gives them up
+        let mut turns = 0;
+        let other = async {
+            loop {
+                match receiver.try_recv() {
+                    Ok(output) => return output,
+                    Err(_) => turns += 1,
+                }
+                YieldNow(false).await;
+            }
+        };
+        let (result, output) = block_on(async { futures::join!(machine, other) });
+        result.unwrap();
+        assert_eq!(5000, output);
+        // three instructions a count is 15000 steps, and a turn given up
+        // every 1000 of them
+        assert_eq!(15, turns);
+    }
+}
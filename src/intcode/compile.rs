@@ -0,0 +1,230 @@
+use super::Memory;
+use std::fmt;
+
+mod generate;
+mod parse;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CompileError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Compiles a program in a small structured language to an Intcode image.
+///
+/// A program is a list of functions and globals:
+///
+/// ```text
+/// var calls = 0;
+///
+/// fn fib(n) {
+///     calls = calls + 1;
+///     if n < 2 { return n; }
+///     return fib(n - 1) + fib(n - 2);
+/// }
+///
+/// fn main() {
+///     var n = input();
+///     while n >= 0 {
+///         output(fib(n));
+///         n = n - 1;
+///     }
+/// }
+/// ```
+///
+/// Everything is a word. Globals start out as the number they're given,
+/// locals are declared with `var` and scoped to their block. Expressions
+/// have `+ - *`, comparisons, `&& || !` (giving 0 or 1) and calls, and
+/// `input()` and `output(x)` are built in. Running starts at `main` and
+/// halts when it returns. `#` starts a comment.
+///
+/// Each call gets a frame on a stack after the globals, addressed through
+/// the relative base: the return address is at `[rb+0]`, then the
+/// arguments, locals and temporaries.
+pub fn compile(source: &str) -> Result<Memory, CompileError> {
+    generate::generate(&parse::parse(source)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Computer;
+    use super::*;
+    use std::fs;
+
+    fn run(source: &str, inputs: &[i64]) -> Vec<i64> {
+        let mut computer = Computer::new(compile(source).unwrap());
+        for input in inputs {
+            computer.try_run().unwrap();
+            computer.input(*input);
+        }
+        computer.try_run().unwrap();
+        assert!(computer.halted());
+        computer.outputs
+    }
+
+    fn example(name: &str, inputs: &[i64]) -> Vec<i64> {
+        let path = format!("data/programs/{}.txt", name);
+        run(&fs::read_to_string(path).unwrap(), inputs)
+    }
+
+    #[test]
+    fn test_expressions() {
+        let source = "fn main() {
+            var a = input();
+            var b = input();
+            output(a + b * 2 - -a);
+            output(a - b);
+            output((a < b) + (a <= b) * 10 + (a > b) * 100 + (a >= b) * 1000);
+            output((a == b) + (a != b) * 10);
+            output(!a + !!b * 10);
+            output((a && b) + (a || 0) * 10 + (0 && a) * 100 + (1 || a) * 1000);
+            output(2 * 3 - 4 < 5 == 1);
+        }";
+        assert_eq!(vec![16, -2, 11, 10, 10, 1011, 1], run(source, &[3, 5]));
+        assert_eq!(vec![8, 4, 1100, 10, 0, 1010, 1], run(source, &[4, 0]));
+    }
+
+    #[test]
+    fn test_control_flow() {
+        let source = "fn main() {
+            var n = input();
+            while n > 0 {
+                if n == 3 {
+                    output(33);
+                } else if n * 2 == 4 {
+                    var n = 22;   # shadows the outer n
+                    output(n);
+                } else {
+                    output(n);
+                }
+                n = n - 1;
+            }
+            while 0 { output(99); }
+            if 1 { output(7); } else { output(8); }
+        }";
+        assert_eq!(vec![5, 4, 33, 22, 1, 7], run(source, &[5]));
+    }
+
+    #[test]
+    fn test_functions() {
+        let source = "var total = 10;
+            fn add(x) { total = total + x; return total; }
+            fn pair(a, b) { return a * 100 + b; }
+            fn nothing() { }
+            fn main() {
+                output(pair(add(1), add(2)));
+                output(pair(total, add(3)));
+                output(nothing());
+                return;
+                output(1);
+            }";
+        assert_eq!(vec![1113, 1316, 0], run(source, &[]));
+    }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(
+            vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55],
+            example("fib", &[10])
+        );
+        assert_eq!(
+            vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47],
+            example("primes", &[50])
+        );
+        assert_eq!(vec![6, 1, 25], example("gcd", &[12, 18, 7, 5, 100, 75, 0]));
+    }
+
+    #[test]
+    fn test_deep_recursion() {
+        let source = "fn sum(n) { if n == 0 { return 0; } return n + sum(n - 1); }
+            fn main() { output(sum(input())); }";
+        assert_eq!(vec![500500], run(source, &[1000]));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source| compile(source).unwrap_err().to_string();
+        assert_eq!("line 1: there's no main function", error("fn f() {}"));
+        assert_eq!("line 1: main can't take arguments", error("fn main(x) {}"));
+        assert_eq!(
+            "line 2: unknown variable y",
+            error("fn main() {\n  output(y);\n}")
+        );
+        assert_eq!("line 1: unknown function f", error("fn main() { f(); }"));
+        assert_eq!(
+            "line 1: f takes 1 arguments, not 2",
+            error("fn f(x) {} fn main() { f(1, 2); }")
+        );
+        assert_eq!(
+            "line 1: x is already declared",
+            error("fn main() { var x; var x; }")
+        );
+        assert_eq!(
+            "line 2: f is already defined",
+            error("fn f() {}\nfn f() {}")
+        );
+        assert_eq!("line 1: output is a builtin", error("fn output() {}"));
+        assert_eq!(
+            "line 1: x is already declared",
+            error("var x; var x; fn main() {}")
+        );
+        assert_eq!(
+            "line 1: duplicate parameter a",
+            error("fn f(a, a) {} fn main() {}")
+        );
+        // branches a constant never takes are still checked
+        assert_eq!(
+            "line 1: unknown variable y",
+            error("fn main() { while 0 { output(y); } }")
+        );
+        assert_eq!(
+            "line 1: unknown function nosuch",
+            error("fn main() { if 0 { nosuch(1, 2); } }")
+        );
+        assert_eq!(
+            "line 1: f takes 0 arguments, not 1",
+            error("fn f() {} fn main() { if 1 { } else { f(1); } }")
+        );
+        assert_eq!(
+            "line 1: unknown variable z",
+            error("fn main() { output(0 && z); }")
+        );
+    }
+
+    #[test]
+    fn test_constant_overflow() {
+        let error = |source: &str| compile(source).unwrap_err().to_string();
+        assert_eq!(
+            "line 3: 9223372036854775807 + 1 doesn't fit in a word",
+            error("fn main() {\n  output(9223372036854775807\n    + 1);\n}")
+        );
+        assert_eq!(
+            "line 1: 3037000500 * 3037000500 doesn't fit in a word",
+            error("fn main() { output(3037000500 * 3037000500); }")
+        );
+        assert_eq!(
+            "line 1: -9223372036854775807 - 2 doesn't fit in a word",
+            error("fn main() { output(-9223372036854775807 - 2); }")
+        );
+        // the most negative word can be made, but not negated
+        let min = "(-9223372036854775807 - 1)";
+        assert_eq!(
+            vec![i64::MIN],
+            run(&format!("fn main() {{ output({}); }}", min), &[])
+        );
+        assert_eq!(
+            "line 2: -(-9223372036854775808) doesn't fit in a word",
+            error(&format!("fn main() {{\n  output(-{});\n}}", min))
+        );
+        // but subtracting it is fine, and wraps like any other word
+        let source = format!("fn main() {{ var x = input(); output(x - {}); }}", min);
+        assert_eq!(vec![i64::MAX], run(&source, &[-1]));
+        assert_eq!(vec![i64::MIN + 5], run(&source, &[5]));
+    }
+}
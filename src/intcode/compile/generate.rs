@@ -0,0 +1,500 @@
+use super::parse::{Expr, Function, Program, Stmt};
+use super::CompileError;
+use crate::intcode::{Memory, Word};
+use std::collections::HashMap;
+
+/// An instruction operand, some of which can only be resolved once the
+/// whole function or program has been generated.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Operand {
+    Immediate(Word),
+    /// the address of a label, as an immediate
+    Label(usize),
+    /// a global's cell, in position mode
+    Global(usize),
+    /// `[rb+n]`, a slot in the current frame
+    Local(Word),
+    /// `[rb+frame+n]`, a slot in the frame of the function being called
+    Callee(Word),
+    /// the current function's frame size times the sign, as an immediate
+    FrameSize(Word),
+    /// where the stack starts, as an immediate
+    StackBase,
+}
+
+use Operand::*;
+
+const ADD: Word = 1;
+const MUL: Word = 2;
+const IN: Word = 3;
+const OUT: Word = 4;
+const JT: Word = 5;
+const JF: Word = 6;
+const LT: Word = 7;
+const EQ: Word = 8;
+const ARB: Word = 9;
+const HLT: Word = 99;
+
+// global 0 is where functions leave their return value
+const RETURN_VALUE: Operand = Global(0);
+
+enum Fixup {
+    Label(usize),
+    Global(usize),
+    Frame(Word, Word),
+    StackBase,
+}
+
+struct Generator<'a> {
+    code: Vec<Word>,
+    fixups: Vec<(usize, Fixup)>,
+    labels: Vec<Option<Word>>,
+    // label and arity
+    functions: HashMap<&'a str, (usize, usize)>,
+    globals: HashMap<&'a str, usize>,
+    initial: Vec<Word>,
+    // per function
+    scopes: Vec<HashMap<&'a str, Word>>,
+    next_slot: Word,
+    frame_size: Word,
+}
+
+fn error<T>(line: usize, message: String) -> Result<T, CompileError> {
+    Err(CompileError { line, message })
+}
+
+// -n, which the most negative word doesn't have
+fn negate(n: Word, line: usize) -> Result<Word, CompileError> {
+    match n.checked_neg() {
+        Some(negated) => Ok(negated),
+        None => error(line, format!("-({}) doesn't fit in a word", n)),
+    }
+}
+
+impl<'a> Generator<'a> {
+    fn emit(&mut self, opcode: Word, operands: &[Operand]) {
+        let start = self.code.len();
+        let mut instruction = opcode;
+        let mut scale = 100;
+        self.code.push(0);
+        for operand in operands {
+            let pos = self.code.len();
+            let (mode, word) = match *operand {
+                Immediate(n) => (1, n),
+                Label(label) => {
+                    self.fixups.push((pos, Fixup::Label(label)));
+                    (1, 0)
+                }
+                Global(index) => {
+                    self.fixups.push((pos, Fixup::Global(index)));
+                    (0, 0)
+                }
+                Local(n) => (2, n),
+                Callee(n) => {
+                    self.fixups.push((pos, Fixup::Frame(1, n)));
+                    (2, 0)
+                }
+                FrameSize(sign) => {
+                    self.fixups.push((pos, Fixup::Frame(sign, 0)));
+                    (1, 0)
+                }
+                StackBase => {
+                    self.fixups.push((pos, Fixup::StackBase));
+                    (1, 0)
+                }
+            };
+            instruction += mode * scale;
+            scale *= 10;
+            self.code.push(word);
+        }
+        self.code[start] = instruction;
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn place(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len() as Word);
+    }
+
+    fn slot(&mut self) -> Word {
+        let slot = self.next_slot;
+        self.next_slot += 1;
+        self.frame_size = self.frame_size.max(self.next_slot);
+        slot
+    }
+
+    fn lookup(&self, name: &str, line: usize) -> Result<Operand, CompileError> {
+        if let Some(slot) = self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            return Ok(Local(*slot));
+        }
+        match self.globals.get(name) {
+            Some(index) => Ok(Global(*index)),
+            None => error(line, format!("unknown variable {}", name)),
+        }
+    }
+
+    /// Generates something only to check its names and arities, then throws
+    /// the code away, for a branch a constant condition never takes.
+    fn discard<T>(
+        &mut self,
+        generate: impl FnOnce(&mut Self) -> Result<T, CompileError>,
+    ) -> Result<(), CompileError> {
+        let (code, fixups, labels) = (self.code.len(), self.fixups.len(), self.labels.len());
+        let (next_slot, frame_size) = (self.next_slot, self.frame_size);
+        generate(self)?;
+        self.code.truncate(code);
+        self.fixups.truncate(fixups);
+        self.labels.truncate(labels);
+        self.next_slot = next_slot;
+        self.frame_size = frame_size;
+        Ok(())
+    }
+
+    /// Computes `value` into a fresh slot with a single instruction.
+    fn compute(&mut self, opcode: Word, a: Operand, b: Operand) -> Operand {
+        let slot = self.slot();
+        self.emit(opcode, &[a, b, Local(slot)]);
+        Local(slot)
+    }
+
+    /// 1 if `value` is nonzero, 0 if it is zero.
+    fn truth(&mut self, value: Operand, dst: Operand) {
+        self.emit(EQ, &[value, Immediate(0), dst]);
+        self.emit(EQ, &[dst, Immediate(0), dst]);
+    }
+
+    fn expr(&mut self, expr: &'a Expr) -> Result<Operand, CompileError> {
+        Ok(match expr {
+            Expr::Number(n) => Immediate(*n),
+            Expr::Var { name, line } => self.lookup(name, *line)?,
+            Expr::Call { name, args, line } => self.call(name, args, *line)?,
+            Expr::Neg(value, line) => match self.expr(value)? {
+                Immediate(n) => Immediate(negate(n, *line)?),
+                value => self.compute(MUL, value, Immediate(-1)),
+            },
+            Expr::Not(value) => match self.expr(value)? {
+                Immediate(n) => Immediate((n == 0) as Word),
+                value => self.compute(EQ, value, Immediate(0)),
+            },
+            Expr::Binary(op, left, right, _) if *op == "&&" || *op == "||" => {
+                let left = self.expr(left)?;
+                let and = *op == "&&";
+                match left {
+                    Immediate(n) if (n != 0) == and => {
+                        let right = self.expr(right)?;
+                        let slot = self.slot();
+                        self.truth(right, Local(slot));
+                        Local(slot)
+                    }
+                    Immediate(_) => {
+                        self.discard(|generator| generator.expr(right))?;
+                        Immediate(!and as Word)
+                    }
+                    _ => {
+                        let slot = self.slot();
+                        let end = self.label();
+                        self.emit(ADD, &[Immediate(!and as Word), Immediate(0), Local(slot)]);
+                        self.emit(if and { JF } else { JT }, &[left, Label(end)]);
+                        let right = self.expr(right)?;
+                        self.truth(right, Local(slot));
+                        self.place(end);
+                        Local(slot)
+                    }
+                }
+            }
+            Expr::Binary(op, left, right, line) => {
+                let left = self.expr(left)?;
+                let right = self.expr(right)?;
+                if let (Immediate(a), Immediate(b)) = (left, right) {
+                    let overflow = || CompileError {
+                        line: *line,
+                        message: format!("{} {} {} doesn't fit in a word", a, op, b),
+                    };
+                    return Ok(Immediate(match *op {
+                        "+" => a.checked_add(b).ok_or_else(overflow)?,
+                        "-" => a.checked_sub(b).ok_or_else(overflow)?,
+                        "*" => a.checked_mul(b).ok_or_else(overflow)?,
+                        "<" => (a < b) as Word,
+                        "<=" => (a <= b) as Word,
+                        ">" => (a > b) as Word,
+                        ">=" => (a >= b) as Word,
+                        "==" => (a == b) as Word,
+                        _ => (a != b) as Word,
+                    }));
+                }
+                match *op {
+                    "+" => self.compute(ADD, left, right),
+                    "-" => {
+                        // -MIN wraps around to MIN, and adding that is still
+                        // the same as subtracting it
+                        let negated = match right {
+                            Immediate(b) => Immediate(b.wrapping_neg()),
+                            right => self.compute(MUL, right, Immediate(-1)),
+                        };
+                        self.compute(ADD, left, negated)
+                    }
+                    "*" => self.compute(MUL, left, right),
+                    "<" => self.compute(LT, left, right),
+                    ">" => self.compute(LT, right, left),
+                    "==" => self.compute(EQ, left, right),
+                    // the negations of the three above
+                    "<=" | ">=" | "!=" => {
+                        let result = match *op {
+                            "<=" => self.compute(LT, right, left),
+                            ">=" => self.compute(LT, left, right),
+                            _ => self.compute(EQ, left, right),
+                        };
+                        self.emit(EQ, &[result, Immediate(0), result]);
+                        result
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        })
+    }
+
+    fn call(&mut self, name: &str, args: &'a [Expr], line: usize) -> Result<Operand, CompileError> {
+        let arity = |expected: usize| {
+            if args.len() == expected {
+                Ok(())
+            } else {
+                error(
+                    line,
+                    format!("{} takes {} arguments, not {}", name, expected, args.len()),
+                )
+            }
+        };
+        match name {
+            "input" => {
+                arity(0)?;
+                let slot = self.slot();
+                self.emit(IN, &[Local(slot)]);
+                return Ok(Local(slot));
+            }
+            "output" => {
+                arity(1)?;
+                let value = self.expr(&args[0])?;
+                self.emit(OUT, &[value]);
+                return Ok(Immediate(0));
+            }
+            _ => (),
+        }
+        let (label, expected) = match self.functions.get(name) {
+            Some(function) => *function,
+            None => return error(line, format!("unknown function {}", name)),
+        };
+        arity(expected)?;
+
+        // a later argument can call something that changes a global, so
+        // everything is copied into this frame before the callee's is set up
+        let mut values = vec![];
+        for arg in args {
+            let value = match self.expr(arg)? {
+                value @ Global(_) => self.compute(ADD, value, Immediate(0)),
+                value => value,
+            };
+            values.push(value);
+        }
+        let back = self.label();
+        self.emit(ADD, &[Label(back), Immediate(0), Callee(0)]);
+        for (i, value) in values.into_iter().enumerate() {
+            self.emit(ADD, &[value, Immediate(0), Callee(1 + i as Word)]);
+        }
+        self.emit(ARB, &[FrameSize(1)]);
+        self.emit(JT, &[Immediate(1), Label(label)]);
+        self.place(back);
+        self.emit(ARB, &[FrameSize(-1)]);
+        Ok(self.compute(ADD, RETURN_VALUE, Immediate(0)))
+    }
+
+    fn block(&mut self, stmts: &'a [Stmt]) -> Result<(), CompileError> {
+        let outer = self.next_slot;
+        self.scopes.push(HashMap::new());
+        for stmt in stmts {
+            self.stmt(stmt)?;
+        }
+        self.scopes.pop();
+        self.next_slot = outer;
+        Ok(())
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) -> Result<(), CompileError> {
+        // temporaries only live until the end of the statement
+        let mark = self.next_slot;
+        match stmt {
+            Stmt::Var { name, value, line } => {
+                let value = match value {
+                    Some(value) => self.expr(value)?,
+                    None => Immediate(0),
+                };
+                self.next_slot = mark;
+                let scope = self.scopes.last_mut().unwrap();
+                if scope.contains_key(name.as_str()) {
+                    return error(*line, format!("{} is already declared", name));
+                }
+                scope.insert(name, mark);
+                let slot = self.slot();
+                self.emit(ADD, &[value, Immediate(0), Local(slot)]);
+                return Ok(());
+            }
+            Stmt::Assign { name, value, line } => {
+                let target = self.lookup(name, *line)?;
+                let value = self.expr(value)?;
+                self.emit(ADD, &[value, Immediate(0), target]);
+            }
+            Stmt::If {
+                cond,
+                then,
+                otherwise,
+            } => match self.expr(cond)? {
+                Immediate(0) => {
+                    self.discard(|generator| generator.block(then))?;
+                    self.block(otherwise)?;
+                }
+                Immediate(_) => {
+                    self.block(then)?;
+                    self.discard(|generator| generator.block(otherwise))?;
+                }
+                cond => {
+                    let (other, end) = (self.label(), self.label());
+                    self.emit(JF, &[cond, Label(other)]);
+                    self.block(then)?;
+                    self.emit(JT, &[Immediate(1), Label(end)]);
+                    self.place(other);
+                    self.block(otherwise)?;
+                    self.place(end);
+                }
+            },
+            Stmt::While { cond, body } => {
+                let (top, end) = (self.label(), self.label());
+                self.place(top);
+                match self.expr(cond)? {
+                    Immediate(0) => self.discard(|generator| generator.block(body))?,
+                    cond => {
+                        if cond != Immediate(1) {
+                            self.emit(JF, &[cond, Label(end)]);
+                        }
+                        self.next_slot = mark;
+                        self.block(body)?;
+                        self.emit(JT, &[Immediate(1), Label(top)]);
+                    }
+                }
+                self.place(end);
+            }
+            Stmt::Return(value) => {
+                let value = match value {
+                    Some(value) => self.expr(value)?,
+                    None => Immediate(0),
+                };
+                self.ret(value);
+            }
+            Stmt::Expr(expr) => {
+                self.expr(expr)?;
+            }
+        }
+        self.next_slot = mark;
+        Ok(())
+    }
+
+    fn ret(&mut self, value: Operand) {
+        self.emit(ADD, &[value, Immediate(0), RETURN_VALUE]);
+        self.emit(JT, &[Immediate(1), Local(0)]);
+    }
+
+    fn function(&mut self, function: &'a Function) -> Result<(), CompileError> {
+        let start = self.fixups.len();
+        let (label, _) = self.functions[function.name.as_str()];
+        self.place(label);
+
+        // the return address is in slot 0, the arguments after it
+        let mut params = HashMap::new();
+        for (i, param) in function.params.iter().enumerate() {
+            if params.insert(param.as_str(), 1 + i as Word).is_some() {
+                return error(function.line, format!("duplicate parameter {}", param));
+            }
+        }
+        self.scopes = vec![params];
+        self.next_slot = 1 + function.params.len() as Word;
+        self.frame_size = self.next_slot;
+        self.block(&function.body)?;
+        self.ret(Immediate(0));
+
+        // calls made from here put the callee's frame right after this one
+        let frame_size = self.frame_size;
+        for (pos, fixup) in &self.fixups[start..] {
+            if let Fixup::Frame(sign, offset) = fixup {
+                self.code[*pos] = sign * frame_size + offset;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn generate(program: &Program) -> Result<Memory, CompileError> {
+    let mut generator = Generator {
+        code: vec![],
+        fixups: vec![],
+        labels: vec![],
+        functions: HashMap::new(),
+        globals: HashMap::new(),
+        initial: vec![0],
+        scopes: vec![],
+        next_slot: 0,
+        frame_size: 0,
+    };
+    for global in &program.globals {
+        let index = generator.initial.len();
+        if generator.globals.insert(&global.name, index).is_some() {
+            return error(global.line, format!("{} is already declared", global.name));
+        }
+        generator.initial.push(global.value);
+    }
+    for function in &program.functions {
+        if function.name == "input" || function.name == "output" {
+            return error(function.line, format!("{} is a builtin", function.name));
+        }
+        let label = generator.label();
+        let entry = (label, function.params.len());
+        if generator.functions.insert(&function.name, entry).is_some() {
+            return error(
+                function.line,
+                format!("{} is already defined", function.name),
+            );
+        }
+    }
+    let main = match generator.functions.get("main") {
+        Some((main, 0)) => *main,
+        Some(_) => return error(1, "main can't take arguments".to_string()),
+        None => return error(1, "there's no main function".to_string()),
+    };
+
+    // main returns to a hlt
+    let halt = generator.label();
+    generator.emit(ARB, &[StackBase]);
+    generator.emit(ADD, &[Label(halt), Immediate(0), Local(0)]);
+    generator.emit(JT, &[Immediate(1), Label(main)]);
+    generator.place(halt);
+    generator.emit(HLT, &[]);
+    for function in &program.functions {
+        generator.function(function)?;
+    }
+
+    let data = generator.code.len() as Word;
+    let stack = data + generator.initial.len() as Word;
+    for (pos, fixup) in &generator.fixups {
+        generator.code[*pos] = match fixup {
+            Fixup::Label(label) => generator.labels[*label].unwrap(),
+            Fixup::Global(index) => data + *index as Word,
+            Fixup::StackBase => stack,
+            Fixup::Frame(..) => continue,
+        };
+    }
+    let mut memory = Memory::empty();
+    for (addr, word) in generator.code.iter().chain(&generator.initial).enumerate() {
+        memory.write(addr as Word, *word);
+    }
+    Ok(memory)
+}
@@ -0,0 +1,435 @@
+use super::CompileError;
+use crate::intcode::Word;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expr {
+    Number(Word),
+    Var {
+        name: String,
+        line: usize,
+    },
+    Call {
+        name: String,
+        args: Vec<Expr>,
+        line: usize,
+    },
+    /// With the line of the `-`, for when a constant can't be negated.
+    Neg(Box<Expr>, usize),
+    Not(Box<Expr>),
+    /// With the line of the operator, for when constants overflow.
+    Binary(&'static str, Box<Expr>, Box<Expr>, usize),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Stmt {
+    Var {
+        name: String,
+        value: Option<Expr>,
+        line: usize,
+    },
+    Assign {
+        name: String,
+        value: Expr,
+        line: usize,
+    },
+    If {
+        cond: Expr,
+        then: Vec<Stmt>,
+        otherwise: Vec<Stmt>,
+    },
+    While {
+        cond: Expr,
+        body: Vec<Stmt>,
+    },
+    Return(Option<Expr>),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Stmt>,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Global {
+    pub name: String,
+    pub value: Word,
+    pub line: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Program {
+    pub functions: Vec<Function>,
+    pub globals: Vec<Global>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+    Number(Word),
+    Ident(String),
+    Punct(&'static str),
+}
+
+// longest first, so `<=` isn't read as `<` then `=`
+const PUNCTUATION: &[&str] = &[
+    "&&", "||", "==", "!=", "<=", ">=", "(", ")", "{", "}", ",", ";", "=", "+", "-", "*", "<", ">",
+    "!",
+];
+
+// binary operators from loosest to tightest
+const PRECEDENCE: &[&[&str]] = &[
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*"],
+];
+
+fn error<T>(line: usize, message: String) -> Result<T, CompileError> {
+    Err(CompileError { line, message })
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, CompileError> {
+    let mut tokens = vec![];
+    for (i, line) in source.lines().enumerate() {
+        let mut rest = line.split('#').next().unwrap().trim_start();
+        while !rest.is_empty() {
+            let c = rest.chars().next().unwrap();
+            let len = if c.is_ascii_digit() {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                match rest[..len].parse() {
+                    Ok(n) => tokens.push((Token::Number(n), i + 1)),
+                    Err(_) => return error(i + 1, format!("number too big: {}", &rest[..len])),
+                }
+                len
+            } else if c.is_alphabetic() || c == '_' {
+                let len = rest
+                    .find(|c: char| !c.is_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                tokens.push((Token::Ident(rest[..len].to_string()), i + 1));
+                len
+            } else {
+                match PUNCTUATION.iter().find(|p| rest.starts_with(*p)) {
+                    Some(punct) => {
+                        tokens.push((Token::Punct(punct), i + 1));
+                        punct.len()
+                    }
+                    None => return error(i + 1, format!("unexpected {:?}", c)),
+                }
+            };
+            rest = rest[len..].trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos).or_else(|| self.tokens.last()) {
+            Some((_, line)) => *line,
+            None => 1,
+        }
+    }
+
+    fn is(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == keyword)
+    }
+
+    fn unexpected<T>(&self, wanted: &str) -> Result<T, CompileError> {
+        let found = match self.peek() {
+            Some(Token::Number(n)) => n.to_string(),
+            Some(Token::Ident(name)) => name.clone(),
+            Some(Token::Punct(punct)) => punct.to_string(),
+            None => "the end".to_string(),
+        };
+        error(self.line(), format!("expected {}, found {}", wanted, found))
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), CompileError> {
+        if !self.is(punct) {
+            return self.unexpected(&format!("{:?}", punct));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<String, CompileError> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => self.unexpected("a name"),
+        }
+    }
+
+    fn program(&mut self) -> Result<Program, CompileError> {
+        let mut program = Program::default();
+        while self.peek().is_some() {
+            let line = self.line();
+            if self.is_keyword("fn") {
+                self.pos += 1;
+                let name = self.ident()?;
+                self.expect("(")?;
+                let mut params = vec![];
+                while !self.is(")") {
+                    if !params.is_empty() {
+                        self.expect(",")?;
+                    }
+                    params.push(self.ident()?);
+                }
+                self.pos += 1;
+                let body = self.block()?;
+                program.functions.push(Function {
+                    name,
+                    params,
+                    body,
+                    line,
+                });
+            } else if self.is_keyword("var") {
+                self.pos += 1;
+                let name = self.ident()?;
+                let mut value = 0;
+                if self.is("=") {
+                    self.pos += 1;
+                    let sign = if self.is("-") {
+                        self.pos += 1;
+                        -1
+                    } else {
+                        1
+                    };
+                    value = match self.peek() {
+                        Some(Token::Number(n)) => match n.checked_mul(sign) {
+                            Some(value) => value,
+                            None => return error(line, format!("{} doesn't fit", n)),
+                        },
+                        _ => return self.unexpected("a number"),
+                    };
+                    self.pos += 1;
+                }
+                self.expect(";")?;
+                program.globals.push(Global { name, value, line });
+            } else {
+                return self.unexpected("fn or var");
+            }
+        }
+        Ok(program)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, CompileError> {
+        self.expect("{")?;
+        let mut stmts = vec![];
+        while !self.is("}") {
+            if self.peek().is_none() {
+                return self.unexpected("\"}\"");
+            }
+            stmts.push(self.stmt()?);
+        }
+        self.pos += 1;
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, CompileError> {
+        let line = self.line();
+        let stmt = if self.is_keyword("var") {
+            self.pos += 1;
+            let name = self.ident()?;
+            let value = if self.is("=") {
+                self.pos += 1;
+                Some(self.expr(0)?)
+            } else {
+                None
+            };
+            Stmt::Var { name, value, line }
+        } else if self.is_keyword("if") {
+            self.pos += 1;
+            let cond = self.expr(0)?;
+            let then = self.block()?;
+            let otherwise = if self.is_keyword("else") {
+                self.pos += 1;
+                if self.is_keyword("if") {
+                    vec![self.stmt()?]
+                } else {
+                    self.block()?
+                }
+            } else {
+                vec![]
+            };
+            return Ok(Stmt::If {
+                cond,
+                then,
+                otherwise,
+            });
+        } else if self.is_keyword("while") {
+            self.pos += 1;
+            let cond = self.expr(0)?;
+            let body = self.block()?;
+            return Ok(Stmt::While { cond, body });
+        } else if self.is_keyword("return") {
+            self.pos += 1;
+            if self.is(";") {
+                Stmt::Return(None)
+            } else {
+                Stmt::Return(Some(self.expr(0)?))
+            }
+        } else if matches!(self.peek(), Some(Token::Ident(_)))
+            && self.tokens.get(self.pos + 1).map(|(token, _)| token) == Some(&Token::Punct("="))
+        {
+            let name = self.ident()?;
+            self.pos += 1;
+            let value = self.expr(0)?;
+            Stmt::Assign { name, value, line }
+        } else {
+            Stmt::Expr(self.expr(0)?)
+        };
+        self.expect(";")?;
+        Ok(stmt)
+    }
+
+    fn expr(&mut self, level: usize) -> Result<Expr, CompileError> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.expr(level + 1)?;
+        loop {
+            let line = self.line();
+            let op = match self.peek() {
+                Some(Token::Punct(punct)) if PRECEDENCE[level].contains(punct) => *punct,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.expr(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right), line);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, CompileError> {
+        if self.is("-") {
+            let line = self.line();
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?), line));
+        }
+        if self.is("!") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let line = self.line();
+        match self.peek() {
+            Some(Token::Number(n)) => {
+                let n = *n;
+                self.pos += 1;
+                Ok(Expr::Number(n))
+            }
+            Some(Token::Punct("(")) => {
+                self.pos += 1;
+                let expr = self.expr(0)?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(_)) => {
+                let name = self.ident()?;
+                if !self.is("(") {
+                    return Ok(Expr::Var { name, line });
+                }
+                self.pos += 1;
+                let mut args = vec![];
+                while !self.is(")") {
+                    if !args.is_empty() {
+                        self.expect(",")?;
+                    }
+                    args.push(self.expr(0)?);
+                }
+                self.pos += 1;
+                Ok(Expr::Call { name, args, line })
+            }
+            _ => self.unexpected("an expression"),
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &["fn", "var", "if", "else", "while", "return"];
+
+pub fn parse(source: &str) -> Result<Program, CompileError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+    };
+    parser.program()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> Box<Expr> {
+        Box::new(Expr::Var {
+            name: name.to_string(),
+            line: 1,
+        })
+    }
+
+    #[test]
+    fn test_precedence() {
+        let program = parse("fn main() { return a + b * -c < d || !e; }").unwrap();
+        let sum = Expr::Binary(
+            "+",
+            var("a"),
+            Box::new(Expr::Binary(
+                "*",
+                var("b"),
+                Box::new(Expr::Neg(var("c"), 1)),
+                1,
+            )),
+            1,
+        );
+        let expected = Expr::Binary(
+            "||",
+            Box::new(Expr::Binary("<", Box::new(sum), var("d"), 1)),
+            Box::new(Expr::Not(var("e"))),
+            1,
+        );
+        assert_eq!(
+            vec![Stmt::Return(Some(expected))],
+            program.functions[0].body
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let error = |source| parse(source).unwrap_err().to_string();
+        assert_eq!(
+            "line 1: expected \";\", found }",
+            error("fn main() { x = 1 }")
+        );
+        assert_eq!(
+            "line 2: expected a name, found while",
+            error("fn main() {\n  var while = 1;\n}")
+        );
+        assert_eq!("line 1: unexpected '$'", error("fn main() { $ }"));
+        assert_eq!(
+            "line 1: expected \"}\", found the end",
+            error("fn main() {")
+        );
+        assert_eq!("line 1: expected a number, found x", error("var g = x;"));
+    }
+}
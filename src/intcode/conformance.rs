@@ -0,0 +1,436 @@
+use super::{Computer, Memory, Trap, Value, Word};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Something that can run Intcode. The conformance suite talks to it in
+/// `Word`s, whatever it uses internally.
+pub trait Backend {
+    /// Runs `program` from the start, feeding it `inputs` in order, until it
+    /// stops or has executed `max_steps` instructions.
+    fn run(&mut self, program: &str, inputs: &[Word], max_steps: u64) -> Outcome;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Stop {
+    Halted,
+    /// Blocked on an input instruction after every input was used up.
+    NeedsInput,
+    StepLimit,
+    Trapped(Trap),
+    /// The backend can't represent the program, an input or an output.
+    Unsupported(String),
+}
+
+impl Stop {
+    // the names fixtures use in `stop:` lines
+    fn name(&self) -> &str {
+        match self {
+            Stop::Halted => "halt",
+            Stop::NeedsInput => "input",
+            Stop::StepLimit => "step-limit",
+            Stop::Trapped(Trap::Overflow { .. }) => "overflow",
+            Stop::Trapped(Trap::BadAddress { .. }) => "bad-address",
+            Stop::Trapped(Trap::IllegalInstruction { .. }) => "illegal-instruction",
+            Stop::Trapped(Trap::UninitializedRead { .. }) => "uninitialized-read",
+            Stop::Unsupported(_) => "unsupported",
+        }
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Trapped(trap) => write!(f, "{} ({})", self.name(), trap),
+            Stop::Unsupported(why) => write!(f, "{} ({})", self.name(), why),
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Outcome {
+    pub stop: Stop,
+    pub outputs: Vec<Word>,
+    /// Every address from 0 to the highest one in use, gaps filled with 0.
+    pub memory: Vec<Word>,
+}
+
+/// The `Computer` in this crate, with `W` as its word type.
+pub struct Interpreter<W = Word> {
+    word: PhantomData<W>,
+}
+
+impl<W: Value> Interpreter<W> {
+    pub fn new() -> Interpreter<W> {
+        Interpreter { word: PhantomData }
+    }
+}
+
+impl<W: Value> Default for Interpreter<W> {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+impl<W: Value> Backend for Interpreter<W> {
+    fn run(&mut self, program: &str, inputs: &[Word], max_steps: u64) -> Outcome {
+        let unsupported = |why: String| Outcome {
+            stop: Stop::Unsupported(why),
+            outputs: vec![],
+            memory: vec![],
+        };
+        let memory = match Memory::<W>::try_parse(program) {
+            Ok(memory) => memory,
+            Err(error) => return unsupported(error.to_string()),
+        };
+        let mut computer = Computer::new(memory);
+        let mut inputs = inputs.iter();
+        let mut steps = 0;
+        let stop = loop {
+            if steps == max_steps {
+                break Stop::StepLimit;
+            }
+            match computer.step() {
+                Ok(true) => steps += 1,
+                Ok(false) if computer.halted() => break Stop::Halted,
+                Ok(false) => match inputs.next() {
+                    Some(&input) => match W::from_i64(input) {
+                        Some(input) => computer.input(input),
+                        None => return unsupported(format!("input {} is too wide", input)),
+                    },
+                    None => break Stop::NeedsInput,
+                },
+                Err(trap) => break Stop::Trapped(trap),
+            }
+        };
+
+        let mut outputs = vec![];
+        for output in &computer.outputs {
+            match output.to_i64() {
+                Some(output) => outputs.push(output),
+                None => return unsupported(format!("output {} is too wide", output)),
+            }
+        }
+        let mut memory = vec![];
+        for addr in 0..computer.memory.len() {
+            match computer.memory.peek(addr).to_i64() {
+                Some(word) => memory.push(word),
+                None => return unsupported(format!("word at {} is too wide", addr)),
+            }
+        }
+        Outcome {
+            stop,
+            outputs,
+            memory,
+        }
+    }
+}
+
+/// One program run and what it should do.
+///
+/// In a fixture file a case starts with its name in brackets, followed by
+/// `key: value` lines:
+///
+/// ```text
+/// # comments and blank lines are ignored
+/// [compare-less-than]
+/// program: 3,9,7,9,10,9,4,9,99,-1,8
+/// input: 5
+/// output: 1
+/// memory: 9=1       # just these addresses
+/// image: 3,9,7,9    # or memory from address 0 on
+/// stop: halt        # the default
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Case {
+    pub name: String,
+    pub program: String,
+    pub inputs: Vec<Word>,
+    /// `None` if the fixture doesn't say, which isn't the same as no outputs.
+    pub outputs: Option<Vec<Word>>,
+    pub memory: Vec<(Word, Word)>,
+    pub image: Option<Vec<Word>>,
+    pub stop: String,
+}
+
+impl Case {
+    fn new(name: &str) -> Case {
+        Case {
+            name: name.to_string(),
+            program: String::new(),
+            inputs: vec![],
+            outputs: None,
+            memory: vec![],
+            image: None,
+            stop: Stop::Halted.name().to_string(),
+        }
+    }
+
+    /// Describes the first way `outcome` differs from what's expected.
+    pub fn check(&self, outcome: &Outcome) -> Result<(), String> {
+        if outcome.stop.name() != self.stop {
+            return Err(format!(
+                "expected {}, stopped with {}",
+                self.stop, outcome.stop
+            ));
+        }
+        if let Some(outputs) = &self.outputs {
+            if *outputs != outcome.outputs {
+                return Err(format!(
+                    "expected outputs {:?}, got {:?}",
+                    outputs, outcome.outputs
+                ));
+            }
+        }
+        let peek = |addr: Word| outcome.memory.get(addr as usize).copied().unwrap_or(0);
+        for &(addr, expected) in &self.memory {
+            if peek(addr) != expected {
+                return Err(format!(
+                    "expected {} at {}, got {}",
+                    expected,
+                    addr,
+                    peek(addr)
+                ));
+            }
+        }
+        if let Some(image) = &self.image {
+            let actual: Vec<Word> = (0..image.len() as Word).map(peek).collect();
+            if *image != actual {
+                return Err(format!("expected memory {:?}, got {:?}", image, actual));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum FixtureError {
+    Io(io::Error),
+    Syntax {
+        file: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FixtureError::Io(error) => write!(f, "{}", error),
+            FixtureError::Syntax {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+        }
+    }
+}
+
+impl From<io::Error> for FixtureError {
+    fn from(error: io::Error) -> Self {
+        FixtureError::Io(error)
+    }
+}
+
+/// A case that didn't do what its fixture expected.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Failure {
+    pub case: String,
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.case, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Suite {
+    pub cases: Vec<Case>,
+}
+
+impl Suite {
+    /// Generous enough for every fixture, but stops a broken backend that
+    /// loops forever.
+    pub const MAX_STEPS: u64 = 100_000;
+
+    /// Reads every `.txt` file in `dir`. Case names are prefixed with the
+    /// file's stem, e.g. `opcodes/add-position`.
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Suite, FixtureError> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut suite = Suite::default();
+        for path in paths {
+            let file = path.file_stem().unwrap().to_string_lossy().to_string();
+            let text = fs::read_to_string(&path)?;
+            suite.cases.extend(Suite::parse(&file, &text)?.cases);
+        }
+        Ok(suite)
+    }
+
+    /// Parses one fixture file. `file` is used for case names and errors.
+    pub fn parse(file: &str, text: &str) -> Result<Suite, FixtureError> {
+        let mut cases: Vec<Case> = vec![];
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| FixtureError::Syntax {
+                file: file.to_string(),
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = format!("{}/{}", file, &line[1..line.len() - 1]);
+                cases.push(Case::new(&name));
+                continue;
+            }
+            let case = match cases.last_mut() {
+                Some(case) => case,
+                None => return Err(error("expected a [case] first".to_string())),
+            };
+            let (key, value) = match line.find(':') {
+                Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                None => return Err(error(format!("expected key: value, not {:?}", line))),
+            };
+            match key {
+                "program" => case.program = value.to_string(),
+                "input" => case.inputs = words(value).map_err(error)?,
+                "output" => case.outputs = Some(words(value).map_err(error)?),
+                "image" => case.image = Some(words(value).map_err(error)?),
+                "stop" => case.stop = value.to_string(),
+                "memory" => {
+                    for cell in value.split(',').filter(|cell| !cell.trim().is_empty()) {
+                        let mut parts = cell.splitn(2, '=');
+                        let addr = words(parts.next().unwrap()).map_err(error)?;
+                        let word = words(parts.next().unwrap_or("")).map_err(error)?;
+                        match (&addr[..], &word[..]) {
+                            ([addr], [word]) => case.memory.push((*addr, *word)),
+                            _ => return Err(error(format!("expected ADDR=VALUE, not {:?}", cell))),
+                        }
+                    }
+                }
+                _ => return Err(error(format!("unknown key {:?}", key))),
+            }
+        }
+        if let Some(case) = cases.iter().find(|case| case.program.is_empty()) {
+            return Err(FixtureError::Syntax {
+                file: file.to_string(),
+                line: 0,
+                message: format!("{} has no program", case.name),
+            });
+        }
+        Ok(Suite { cases })
+    }
+
+    /// Runs every case against `backend`, returning the ones it got wrong.
+    pub fn run<B: Backend + ?Sized>(&self, backend: &mut B) -> Vec<Failure> {
+        self.cases
+            .iter()
+            .filter_map(|case| {
+                let outcome = backend.run(&case.program, &case.inputs, Suite::MAX_STEPS);
+                case.check(&outcome).err().map(|message| Failure {
+                    case: case.name.clone(),
+                    message,
+                })
+            })
+            .collect()
+    }
+}
+
+// comma separated, and an empty list is fine
+fn words(text: &str) -> Result<Vec<Word>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(|word| word.parse().map_err(|_| format!("bad number {:?}", word)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::BigInt;
+
+    fn suite() -> Suite {
+        Suite::load("data/conformance").unwrap()
+    }
+
+    fn assert_passes<B: Backend>(mut backend: B) {
+        let failures = suite().run(&mut backend);
+        let failures: Vec<String> = failures.iter().map(Failure::to_string).collect();
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_word_types() {
+        assert!(suite().cases.len() > 100);
+        assert_passes(Interpreter::<i64>::new());
+        assert_passes(Interpreter::<i128>::new());
+        assert_passes(Interpreter::<BigInt>::new());
+    }
+
+    #[test]
+    fn test_narrow_words() {
+        let failures = suite().run(&mut Interpreter::<i32>::new());
+        assert!(!failures.is_empty());
+        assert!(failures
+            .iter()
+            .all(|failure| failure.case.contains("large")));
+    }
+
+    // drops the last output
+    struct Broken;
+
+    impl Backend for Broken {
+        fn run(&mut self, program: &str, inputs: &[Word], max_steps: u64) -> Outcome {
+            let mut outcome = Interpreter::<Word>::new().run(program, inputs, max_steps);
+            outcome.outputs.pop();
+            outcome
+        }
+    }
+
+    #[test]
+    fn test_failures() {
+        let suite =
+            Suite::parse("test", "[echo]\nprogram: 3,0,4,0,99\ninput: 5\noutput: 5\n").unwrap();
+        assert_eq!(
+            vec![Failure {
+                case: "test/echo".to_string(),
+                message: "expected outputs [5], got []".to_string()
+            }],
+            suite.run(&mut Broken)
+        );
+        assert!(suite.run(&mut Interpreter::<Word>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        match Suite::parse("test", "[a]\nprogram: 99\n\nmemory: 4\n") {
+            Err(FixtureError::Syntax { line: 4, .. }) => (),
+            other => panic!("expected an error on line 4, got {:?}", other),
+        }
+        match Suite::parse("test", "program: 99\n") {
+            Err(FixtureError::Syntax { line: 1, .. }) => (),
+            other => panic!("expected an error on line 1, got {:?}", other),
+        }
+        match Suite::parse("test", "[a]\noutput: 1\n") {
+            Err(FixtureError::Syntax { message, .. }) => {
+                assert_eq!("test/a has no program", message)
+            }
+            other => panic!("expected a missing program, got {:?}", other),
+        }
+    }
+}
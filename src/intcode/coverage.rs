@@ -0,0 +1,181 @@
+use super::{disassemble_one, Memory, Value, Word};
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Which addresses a program touched, and how. Merge the coverage of several
+/// runs to see what a whole test suite exercised.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Coverage {
+    /// Addresses an instruction started at.
+    pub executed: BTreeSet<Word>,
+    /// Addresses read as operands through position or relative mode.
+    pub read: BTreeSet<Word>,
+    pub written: BTreeSet<Word>,
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage::default()
+    }
+
+    pub fn merge(&mut self, other: &Coverage) {
+        self.executed.extend(&other.executed);
+        self.read.extend(&other.read);
+        self.written.extend(&other.written);
+    }
+
+    /// Instructions in `memory` that decode but were never executed and
+    /// weren't touched as data either, as (address, disassembly) pairs.
+    ///
+    /// Code is found by walking the image linearly from 0, skipping over
+    /// executed instructions and cells used as data, so a jump into the middle
+    /// of an unexecuted instruction can throw it off.
+    pub fn unexecuted<W: Value>(&self, memory: &Memory<W>) -> Vec<(Word, String)> {
+        self.annotate(memory)
+            .into_iter()
+            .filter(|line| line.kind == Kind::Unexecuted)
+            .map(|line| (line.addr, line.text))
+            .collect()
+    }
+
+    /// A disassembly of `memory` with every line marked:
+    ///
+    /// ```text
+    /// x   0  add [9], [10], [3]    executed
+    /// !   4  out [3]               decodes, but never executed
+    ///  rw 9  data 30               read and written as data
+    /// ```
+    pub fn annotated<W: Value>(&self, memory: &Memory<W>) -> String {
+        self.annotate(memory)
+            .iter()
+            .map(|line| {
+                let mark = match line.kind {
+                    Kind::Executed => 'x',
+                    Kind::Unexecuted => '!',
+                    Kind::Data => ' ',
+                };
+                let read = if self.read.contains(&line.addr) {
+                    'r'
+                } else {
+                    ' '
+                };
+                let written = if self.written.contains(&line.addr) {
+                    'w'
+                } else {
+                    ' '
+                };
+                format!(
+                    "{}{}{} {:>5}  {}",
+                    mark, read, written, line.addr, line.text
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// A one-line summary, e.g. `executed 5 of 7 instructions (71%), 2 data cells read, 1 written`.
+    pub fn report<W: Value>(&self, memory: &Memory<W>) -> String {
+        let lines = self.annotate(memory);
+        let executed = lines.iter().filter(|l| l.kind == Kind::Executed).count();
+        let code = executed + lines.iter().filter(|l| l.kind == Kind::Unexecuted).count();
+        let percent = (executed * 100).checked_div(code).unwrap_or(100);
+        format!(
+            "executed {} of {} instructions ({}%), {} data cells read, {} written",
+            executed,
+            code,
+            percent,
+            self.read.len(),
+            self.written.len()
+        )
+    }
+
+    fn annotate<W: Value>(&self, memory: &Memory<W>) -> Vec<Line> {
+        let mut lines = vec![];
+        let mut addr = 0;
+        while addr < memory.len() {
+            let is_data = self.read.contains(&addr) || self.written.contains(&addr);
+            let decoded = disassemble_one(memory, addr);
+            let (kind, text, len) = match decoded {
+                Some((text, len)) if self.executed.contains(&addr) => (Kind::Executed, text, len),
+                Some((text, len)) if !is_data => (Kind::Unexecuted, text, len),
+                _ => (Kind::Data, format!("data {}", memory.peek(addr)), 1),
+            };
+            lines.push(Line { addr, kind, text });
+            addr += len;
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Kind {
+    Executed,
+    Unexecuted,
+    Data,
+}
+
+struct Line {
+    addr: Word,
+    kind: Kind,
+    text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Computer;
+    use super::*;
+
+    // outputs 1 if the input is less than 8, otherwise 0
+    const PROGRAM: &str = "3,15,1007,15,8,15,1005,15,12,104,0,99,104,1,99,0";
+
+    fn run(input: Word) -> Coverage {
+        let mut computer = Computer::load(PROGRAM);
+        computer.coverage = Some(Coverage::new());
+        computer.run_with_input(input);
+        computer.coverage.unwrap()
+    }
+
+    #[test]
+    fn test_coverage() {
+        let memory: Memory = Memory::load(PROGRAM);
+        let coverage = run(3);
+        assert_eq!(
+            vec![0, 2, 6, 12, 14]
+                .into_iter()
+                .collect::<BTreeSet<Word>>(),
+            coverage.executed
+        );
+        assert_eq!(
+            vec![(9, "out 0".to_string()), (11, "hlt".to_string())],
+            coverage.unexecuted(&memory)
+        );
+        assert_eq!(
+            "executed 5 of 7 instructions (71%), 1 data cells read, 1 written",
+            coverage.report(&memory)
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let memory: Memory = Memory::load(PROGRAM);
+        let mut coverage = run(3);
+        coverage.merge(&run(10));
+        assert_eq!(0, coverage.unexecuted(&memory).len());
+        assert_eq!(
+            [
+                "x       0  in [15]",
+                "x       2  lt [15], 8, [15]",
+                "x       6  jt [15], 12",
+                "x       9  out 0",
+                "x      11  hlt",
+                "x      12  out 1",
+                "x      14  hlt",
+                " rw    15  data 0",
+            ]
+            .join("\n"),
+            coverage.annotated(&memory)
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use super::{Memory, Value, Word};
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Host hardware that a range of addresses is wired to. Offsets are relative
+/// to the start of the mapped range.
+pub trait Device<W>: Send {
+    /// How many cells it has, at offsets 0 up to this.
+    fn len(&self) -> Word;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn read(&mut self, offset: Word) -> W;
+    fn write(&mut self, offset: Word, value: W);
+    /// Called once per executed instruction.
+    fn tick(&mut self) {}
+}
+
+/// Shared so the host can keep a handle to look at a device after the run.
+pub type SharedDevice<W> = Arc<Mutex<dyn Device<W>>>;
+
+#[derive(Clone)]
+pub(super) struct Mapping<W> {
+    range: Range<Word>,
+    device: SharedDevice<W>,
+}
+
+impl<W> fmt::Debug for Mapping<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mapping({:?})", self.range)
+    }
+}
+
+impl<W: Value> Memory<W> {
+    /// Wires `range` to `device`. Panics if it overlaps something already
+    /// mapped or is longer than the device. Clones of the memory share the
+    /// device rather than copying it.
+    pub fn map(&mut self, range: Range<Word>, device: SharedDevice<W>) {
+        let len = device.lock().unwrap().len();
+        if range.end.saturating_sub(range.start) > len {
+            panic!("{:?} is longer than the device's {} cells", range, len);
+        }
+        if let Some(other) = self
+            .devices
+            .iter()
+            .find(|m| m.range.start < range.end && range.start < m.range.end)
+        {
+            panic!("{:?} overlaps device at {:?}", range, other.range);
+        }
+        self.devices.push(Mapping { range, device });
+    }
+
+    pub fn has_devices(&self) -> bool {
+        !self.devices.is_empty()
+    }
+
+    pub(super) fn device_at(&self, ptr: Word) -> Option<(&SharedDevice<W>, Word)> {
+        self.devices
+            .iter()
+            .find(|m| m.range.contains(&ptr))
+            .map(|m| (&m.device, ptr - m.range.start))
+    }
+
+    pub(super) fn tick(&self) {
+        for mapping in &self.devices {
+            mapping.device.lock().unwrap().tick();
+        }
+    }
+}
+
+/// A grid of cells, row by row. Reads give back what was last written.
+pub struct Framebuffer<W> {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<W>,
+}
+
+impl<W: Value> Framebuffer<W> {
+    pub fn new(width: usize, height: usize) -> Framebuffer<W> {
+        Framebuffer {
+            width,
+            height,
+            cells: vec![W::zero(); width * height],
+        }
+    }
+
+    pub fn size(&self) -> Word {
+        (self.width * self.height) as Word
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &W {
+        &self.cells[y * self.width + x]
+    }
+
+    pub fn cells(&self) -> &[W] {
+        &self.cells
+    }
+
+    /// Draws every cell with `draw`, one line per row.
+    pub fn render<F: Fn(&W) -> char>(&self, draw: F) -> String {
+        self.cells
+            .chunks(self.width)
+            .map(|row| row.iter().map(&draw).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl<W: Value + Send> Device<W> for Framebuffer<W> {
+    fn len(&self) -> Word {
+        self.size()
+    }
+
+    fn read(&mut self, offset: Word) -> W {
+        self.cells[offset as usize].clone()
+    }
+
+    fn write(&mut self, offset: Word, value: W) {
+        self.cells[offset as usize] = value;
+    }
+}
+
+/// A single register. Reading it takes the next key press, or 0 if there
+/// isn't one; writes are ignored.
+pub struct Keyboard<W> {
+    keys: VecDeque<W>,
+}
+
+impl<W: Value> Keyboard<W> {
+    pub fn new() -> Keyboard<W> {
+        Keyboard {
+            keys: VecDeque::new(),
+        }
+    }
+
+    pub fn press(&mut self, key: W) {
+        self.keys.push_back(key);
+    }
+}
+
+impl<W: Value> Default for Keyboard<W> {
+    fn default() -> Keyboard<W> {
+        Keyboard::new()
+    }
+}
+
+impl<W: Value + Send> Device<W> for Keyboard<W> {
+    fn len(&self) -> Word {
+        1
+    }
+
+    fn read(&mut self, _offset: Word) -> W {
+        self.keys.pop_front().unwrap_or_else(W::zero)
+    }
+
+    fn write(&mut self, _offset: Word, _value: W) {}
+}
+
+/// A single register counting executed instructions. Writing sets the count.
+#[derive(Default)]
+pub struct Timer {
+    ticks: Word,
+}
+
+impl Timer {
+    pub fn new() -> Timer {
+        Timer { ticks: 0 }
+    }
+}
+
+impl<W: Value> Device<W> for Timer {
+    fn len(&self) -> Word {
+        1
+    }
+
+    fn read(&mut self, _offset: Word) -> W {
+        W::from_i64(self.ticks).unwrap_or_else(W::zero)
+    }
+
+    fn write(&mut self, _offset: Word, value: W) {
+        self.ticks = value.to_i64().unwrap_or(0);
+    }
+
+    fn tick(&mut self) {
+        self.ticks += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Computer;
+    use super::*;
+
+    #[test]
+    fn test_framebuffer_and_keyboard() {
+        // copy three key presses from the keyboard at 100 into the
+        // framebuffer at 200..203, by hand-unrolled add-with-zero moves
+        let mut memory: Memory =
+            Memory::load("1101,0,0,0,1001,100,0,200,1001,100,0,201,1001,100,0,202,99");
+        let screen = Arc::new(Mutex::new(Framebuffer::new(3, 1)));
+        let keyboard = Arc::new(Mutex::new(Keyboard::new()));
+        memory.map(200..203, screen.clone());
+        memory.map(100..101, keyboard.clone());
+        for key in "hi!".chars() {
+            keyboard.lock().unwrap().press(key as Word);
+        }
+
+        let mut computer = Computer::new(memory);
+        computer.run();
+        let screen = screen.lock().unwrap();
+        assert_eq!("hi!", screen.render(|c| *c as u8 as char));
+        assert_eq!(&('i' as Word), screen.get(1, 0));
+        // device cells don't show up in the memory dump
+        assert_eq!(17, computer.memory.len());
+    }
+
+    #[test]
+    fn test_timer() {
+        // busy-wait until the timer at 50 reaches 10, then output it
+        let mut memory: Memory = Memory::load("1007,50,10,20,1005,20,0,4,50,99");
+        memory.map(50..51, Arc::new(Mutex::new(Timer::new())));
+        let mut computer = Computer::new(memory);
+        computer.run();
+        // the compare sees 10 on its sixth pass, then it and the jump tick twice more
+        assert_eq!(vec![12], computer.outputs);
+    }
+
+    #[test]
+    fn test_clones_share_devices() {
+        let mut memory: Memory = Memory::load("99");
+        let screen = Arc::new(Mutex::new(Framebuffer::new(2, 1)));
+        memory.map(10..12, screen.clone());
+        let mut copy = memory.clone();
+        copy.write(11, 5);
+        copy.write(0, 1);
+        assert_eq!(5, memory.read(11));
+        assert_eq!(99, memory.read(0));
+        assert!(copy.has_devices());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overlap() {
+        let mut memory: Memory = Memory::load("99");
+        memory.map(10..20, Arc::new(Mutex::new(Timer::new())));
+        memory.map(15..16, Arc::new(Mutex::new(Keyboard::new())));
+    }
+
+    #[test]
+    #[should_panic(expected = "0..100 is longer than the device's 3 cells")]
+    fn test_too_long() {
+        let mut memory: Memory = Memory::load("99");
+        memory.map(0..100, Arc::new(Mutex::new(Framebuffer::new(3, 1))));
+    }
+}
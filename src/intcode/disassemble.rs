@@ -0,0 +1,98 @@
+use super::{Memory, Mode, Opcode, Value, Word};
+
+impl Opcode {
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Add(..) => "add",
+            Opcode::Mult(..) => "mul",
+            Opcode::Input(_) => "in",
+            Opcode::Output(_) => "out",
+            Opcode::JIT(..) => "jt",
+            Opcode::JIF(..) => "jf",
+            Opcode::LT(..) => "lt",
+            Opcode::Eq(..) => "eq",
+            Opcode::ARB(_) => "arb",
+            Opcode::Halt => "hlt",
+        }
+    }
+}
+
+fn operand<W: Value>(mode: &Mode, value: &W) -> String {
+    match mode {
+        Mode::Position => format!("[{}]", value),
+        Mode::Immediate => value.to_string(),
+        Mode::Relative if *value < W::zero() => format!("[rb{}]", value),
+        Mode::Relative => format!("[rb+{}]", value),
+    }
+}
+
+/// Disassembles the instruction at `addr`, returning its text and length.
+/// Returns `None` if the cell there doesn't hold a valid instruction, or one
+/// whose operands would run past the last address.
+///
+/// Position mode operands are written `[4]`, relative ones `[rb+4]` and
+/// immediates as bare numbers, e.g. `add [4], 3, [rb-1]`.
+pub fn disassemble_one<W: Value>(memory: &Memory<W>, addr: Word) -> Option<(String, Word)> {
+    let opcode = Opcode::new(memory.mem.get(&addr)?.to_i64()?)?;
+    let modes = opcode.modes();
+    let operands = modes
+        .iter()
+        .enumerate()
+        .map(|(i, mode)| {
+            Some(operand(
+                mode,
+                &memory.peek(addr.checked_add(1 + i as Word)?),
+            ))
+        })
+        .collect::<Option<Vec<String>>>()?;
+    let text = if operands.is_empty() {
+        opcode.mnemonic().to_string()
+    } else {
+        format!("{} {}", opcode.mnemonic(), operands.join(", "))
+    };
+    Some((text, 1 + modes.len() as Word))
+}
+
+/// Disassembles `start..end` linearly, one line per instruction. Cells that
+/// aren't valid instructions are printed as data.
+pub fn disassemble<W: Value>(memory: &Memory<W>, start: Word, end: Word) -> Vec<(Word, String)> {
+    let mut lines = vec![];
+    let mut addr = start;
+    while addr < end {
+        let len = match disassemble_one(memory, addr) {
+            Some((text, len)) => {
+                lines.push((addr, text));
+                len
+            }
+            None => {
+                lines.push((addr, format!("data {}", memory.peek(addr))));
+                1
+            }
+        };
+        addr = match addr.checked_add(len) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble() {
+        let memory: Memory = Memory::load("1002,4,3,4,33,109,-1,204,1,99");
+        assert_eq!(
+            vec![
+                (0, "mul [4], 3, [4]".to_string()),
+                (4, "data 33".to_string()),
+                (5, "arb -1".to_string()),
+                (7, "out [rb+1]".to_string()),
+                (9, "hlt".to_string()),
+            ],
+            disassemble(&memory, 0, 10)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use super::{Memory, Value, Word};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// `index` counts tokens, not characters, so it's also the address the
+    /// word would have been loaded at.
+    BadToken {
+        index: usize,
+        token: String,
+    },
+    /// A binary image whose length isn't a multiple of eight bytes.
+    Truncated {
+        len: usize,
+    },
+    /// A word that doesn't fit in the binary format's 64 bits.
+    TooWide {
+        addr: Word,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "{}", error),
+            LoadError::BadToken { index, token } => {
+                write!(f, "can't parse token {} ({:?})", index, token)
+            }
+            LoadError::Truncated { len } => {
+                write!(f, "binary image of {} bytes isn't whole words", len)
+            }
+            LoadError::TooWide { addr } => write!(f, "word at {} doesn't fit in 64 bits", addr),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    /// Comma and/or whitespace separated words. `#` starts a comment that
+    /// runs to the end of the line.
+    Text,
+    /// Little-endian 64 bit words, starting at address 0.
+    Binary,
+}
+
+impl Format {
+    /// Guesses from the file extension: `.bin` is binary, anything else is text.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Format {
+        match path.as_ref().extension() {
+            Some(ext) if ext == "bin" => Format::Binary,
+            _ => Format::Text,
+        }
+    }
+}
+
+impl<W: Value> Memory<W> {
+    pub fn try_parse(text: &str) -> Result<Memory<W>, LoadError> {
+        let tokens = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap())
+            .flat_map(|line| line.split(|c: char| c == ',' || c.is_whitespace()))
+            .filter(|token| !token.is_empty());
+
+        let mut memory = Memory::empty();
+        for (index, token) in tokens.enumerate() {
+            match token.parse::<W>() {
+                Ok(word) => memory.write(index as Word, word),
+                Err(_) => {
+                    return Err(LoadError::BadToken {
+                        index,
+                        token: token.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(memory)
+    }
+
+    pub fn read_from<R: Read>(mut reader: R, format: Format) -> Result<Memory<W>, LoadError> {
+        match format {
+            Format::Text => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Memory::try_parse(&text)
+            }
+            Format::Binary => {
+                let mut bytes = vec![];
+                reader.read_to_end(&mut bytes)?;
+                Memory::from_le_bytes(&bytes)
+            }
+        }
+    }
+
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Memory<W>, LoadError> {
+        let format = Format::from_path(&path);
+        Memory::read_from(fs::File::open(path)?, format)
+    }
+
+    pub fn from_le_bytes(bytes: &[u8]) -> Result<Memory<W>, LoadError> {
+        if !bytes.len().is_multiple_of(8) {
+            return Err(LoadError::Truncated { len: bytes.len() });
+        }
+        let mut memory = Memory::empty();
+        for (i, chunk) in bytes.chunks(8).enumerate() {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            let word = i64::from_le_bytes(word);
+            // every Value can hold an i64 except narrower primitives
+            match W::from_i64(word) {
+                Some(word) => memory.write(i as Word, word),
+                None => return Err(LoadError::TooWide { addr: i as Word }),
+            }
+        }
+        Ok(memory)
+    }
+
+    /// Writes every address from 0 to the highest one in use, filling gaps with 0.
+    pub fn write_le_bytes<O: Write>(&self, mut out: O) -> Result<(), LoadError> {
+        let end = self.mem.keys().max().map_or(0, |max| max + 1);
+        for addr in 0..end {
+            let word = match self.mem.get(&addr) {
+                Some(word) => word.to_i64().ok_or(LoadError::TooWide { addr })?,
+                None => 0,
+            };
+            out.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitespace_and_comments() {
+        let memory: Memory = Memory::try_parse("1, 0,0,\n  0,99\n").unwrap();
+        assert_eq!("1,0,0,0,99", memory.print());
+
+        let text = "# add the first cell to itself\n1,0,0,0 # store it there too\n\n99\n";
+        let memory: Memory = Memory::try_parse(text).unwrap();
+        assert_eq!("1,0,0,0,99", memory.print());
+    }
+
+    #[test]
+    fn test_bad_token() {
+        match Memory::<Word>::try_parse("1,0,\n0,x,99") {
+            Err(LoadError::BadToken { index, token }) => {
+                assert_eq!(3, index);
+                assert_eq!("x", token);
+            }
+            _ => panic!("expected a bad token"),
+        }
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let mut memory: Memory = Memory::load("104,1125899906842624,99");
+        memory.write(5, -1);
+        let mut bytes = vec![];
+        memory.write_le_bytes(&mut bytes).unwrap();
+        assert_eq!(48, bytes.len());
+
+        let loaded: Memory = Memory::read_from(&bytes[..], Format::Binary).unwrap();
+        assert_eq!("104,1125899906842624,99,0,0,-1", loaded.print());
+
+        match Memory::<Word>::from_le_bytes(&bytes[..7]) {
+            Err(LoadError::Truncated { len: 7 }) => (),
+            _ => panic!("expected a truncated image"),
+        }
+        match Memory::<i32>::from_le_bytes(&bytes) {
+            Err(LoadError::TooWide { addr: 1 }) => (),
+            _ => panic!("expected a word too wide for i32"),
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::Binary, Format::from_path("data/day9.bin"));
+        assert_eq!(Format::Text, Format::from_path("data/day9.txt"));
+    }
+}
@@ -0,0 +1,208 @@
+use super::Word;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A place where the program writes into its own instructions: the
+/// instruction at `pc` writes to `addr`, which is (at some point) executed as
+/// an opcode or operand.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Site {
+    pub pc: Word,
+    pub addr: Word,
+    pub writes: u64,
+    /// Some write happened before `addr` was executed, i.e. the program
+    /// patched code it was about to run.
+    pub before_execution: bool,
+    /// Some write happened after `addr` had already been executed.
+    pub after_execution: bool,
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let when = match (self.before_execution, self.after_execution) {
+            (true, true) => "before and after it runs",
+            (true, false) => "before it runs",
+            _ => "after it ran",
+        };
+        write!(
+            f,
+            "{:>5} writes to {:>5} ({} times), {}",
+            self.pc, self.addr, self.writes, when
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+struct Span {
+    first: u64,
+    last: u64,
+    count: u64,
+}
+
+impl Span {
+    fn new(step: u64) -> Span {
+        Span {
+            first: step,
+            last: step,
+            count: 1,
+        }
+    }
+
+    fn record(&mut self, step: u64) {
+        self.last = step;
+        self.count += 1;
+    }
+}
+
+/// Watches for self-modifying code. Every cell an executed instruction
+/// occupied is code, and any write to a code cell, whether it lands before or
+/// after the cell runs, is a self-modifying site.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Modifications {
+    step: u64,
+    code: BTreeMap<Word, Span>,
+    #[cfg_attr(feature = "serialize", serde(with = "pairs"))]
+    writes: BTreeMap<(Word, Word), Span>,
+}
+
+// JSON keys have to be strings, so the writes go out as a list of
+// ((pc, addr), span) pairs instead of a map
+#[cfg(feature = "serialize")]
+mod pairs {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        writes: &BTreeMap<(Word, Word), Span>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(writes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<(Word, Word), Span>, D::Error> {
+        let pairs: Vec<((Word, Word), Span)> = Vec::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+impl Modifications {
+    pub fn new() -> Modifications {
+        Modifications::default()
+    }
+
+    pub(super) fn executed(&mut self, pc: Word, len: Word) {
+        for addr in pc..pc + len {
+            let step = self.step;
+            self.code
+                .entry(addr)
+                .and_modify(|span| span.record(step))
+                .or_insert_with(|| Span::new(step));
+        }
+        self.step += 1;
+    }
+
+    pub(super) fn written(&mut self, pc: Word, addr: Word) {
+        let step = self.step;
+        self.writes
+            .entry((pc, addr))
+            .and_modify(|span| span.record(step))
+            .or_insert_with(|| Span::new(step));
+    }
+
+    /// Every self-modifying site seen so far, ordered by writer then target.
+    pub fn sites(&self) -> Vec<Site> {
+        let mut sites: Vec<Site> = self
+            .writes
+            .iter()
+            .filter_map(|(&(pc, addr), write)| {
+                let code = self.code.get(&addr)?;
+                Some(Site {
+                    pc,
+                    addr,
+                    writes: write.count,
+                    before_execution: write.first < code.last,
+                    // a write during step n lands after step n fetched its operands
+                    after_execution: write.last >= code.first,
+                })
+            })
+            .collect();
+        sites.sort_by_key(|site| (site.pc, site.addr));
+        sites
+    }
+
+    pub fn report(&self) -> String {
+        let sites = self.sites();
+        if sites.is_empty() {
+            return "no self-modifying code".to_string();
+        }
+        sites
+            .iter()
+            .map(|site| site.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Computer;
+    use super::*;
+
+    fn run(program: &str) -> Vec<Site> {
+        let mut computer = Computer::load(program);
+        computer.modifications = Some(Modifications::new());
+        computer.run();
+        computer.modifications.unwrap().sites()
+    }
+
+    #[test]
+    fn test_patch_before_running() {
+        // writes 99 over the add at 4, so the program halts there
+        let sites = run("1101,90,9,4,1,0,0,0,99");
+        assert_eq!(
+            vec![Site {
+                pc: 0,
+                addr: 4,
+                writes: 1,
+                before_execution: true,
+                after_execution: false,
+            }],
+            sites
+        );
+    }
+
+    #[test]
+    fn test_overwrite_after_running() {
+        // the add at 0 runs, then the add at 4 overwrites its operand
+        let sites = run("1101,1,1,9,1101,5,5,1,99,0");
+        assert_eq!(1, sites.len());
+        assert_eq!(4, sites[0].pc);
+        assert_eq!(1, sites[0].addr);
+        assert!(!sites[0].before_execution);
+        assert!(sites[0].after_execution);
+    }
+
+    #[test]
+    fn test_plain_data() {
+        assert_eq!(Vec::<Site>::new(), run("1101,1,1,5,99,0"));
+    }
+
+    #[test]
+    fn test_day13_program() {
+        // the arcade cabinet computes a tile's address into the operand at
+        // 593 before reading through it; run until it wants a joystick move
+        let program = std::fs::read_to_string("data/day13.txt").unwrap();
+        let mut computer = Computer::load(&program);
+        computer.modifications = Some(Modifications::new());
+        computer.run();
+        let sites = computer.modifications.unwrap().sites();
+        let targets: Vec<Word> = sites.iter().map(|site| site.addr).collect();
+        assert_eq!(vec![593, 593, 593], targets);
+        assert!(sites.iter().all(|site| site.before_execution));
+    }
+}
@@ -0,0 +1,792 @@
+use super::{disassemble_one, Memory, Mode, Opcode, Overflow, Word};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+
+/// A peephole optimizer. Instructions are rewritten in place into simpler
+/// ones no longer than they were, so every address, jump target and
+/// self-modifying write means what it did before:
+///
+/// - position operands reading cells that are never written become immediates
+/// - `add x, 0` and `mul x, 1` become plain copies, and arithmetic or
+///   comparisons on constants become copies of the result
+/// - jumps whose condition is constant, or that go to the next instruction,
+///   become unconditional jumps or no-ops
+/// - a run of instructions that make no difference, i.e. no-ops and stores
+///   that are always overwritten before anything reads them, is jumped over
+/// - jumps to an unconditional jump or to such a run go straight past it
+///
+/// The last two run fewer instructions; the rest make the code easier to read
+/// and analyze, but take the same number of steps. Since skipped stores are
+/// never made, memory can differ from an unoptimized run's along the way,
+/// though not once it halts.
+///
+/// An instruction is only touched if no instruction ever writes to it or reads
+/// it as data. Code is found by following execution from 0. By default that's
+/// all that's assumed, which leaves programs that write through computed
+/// addresses or jump to them alone, since those could go anywhere; `data` and
+/// `assume_returns` let the caller say where they go. Memory-mapped devices
+/// aren't taken into account.
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    /// Where the program keeps data it reaches through addresses computed at
+    /// run time, i.e. relative operands or operands it overwrites, if the
+    /// caller knows. Those addresses are assumed to land in these ranges, and
+    /// nothing in them is executed. Nothing checks that, so a wrong range can
+    /// break the program. Left empty, computed addresses could be anywhere.
+    pub data: Vec<Range<Word>>,
+    /// Assume jumps through computed addresses only land on an immediate jump
+    /// target or on a constant the program stores, which is how return
+    /// addresses are pushed. Also unchecked. Left off, a program with such a
+    /// jump isn't changed at all.
+    pub assume_returns: bool,
+    /// The overflow policy the program will run with. With `Overflow::Trap`,
+    /// arithmetic that might overflow is never skipped.
+    pub overflow: Overflow,
+}
+
+/// One instruction the optimizer changed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rewrite {
+    pub addr: Word,
+    pub before: String,
+    pub after: String,
+}
+
+impl fmt::Display for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>5}  {}  ->  {}", self.addr, self.before, self.after)
+    }
+}
+
+impl Default for Optimizer {
+    fn default() -> Optimizer {
+        Optimizer::new()
+    }
+}
+
+impl Optimizer {
+    pub fn new() -> Optimizer {
+        Optimizer {
+            data: vec![],
+            assume_returns: false,
+            overflow: Overflow::Wrap,
+        }
+    }
+
+    /// Returns the optimized program and what was changed, in address order.
+    pub fn optimize(&self, program: &Memory) -> (Memory, Vec<Rewrite>) {
+        let analysis = Analysis::new(program, self);
+        let mut optimized = program.clone();
+        for (&addr, instruction) in &analysis.instructions {
+            if analysis.fixed(addr) {
+                if let Some(cells) = analysis.simplify(instruction) {
+                    store(&mut optimized, addr, &cells);
+                }
+            }
+        }
+        // the first of a run that makes no difference jumps past the rest,
+        // which are left be for anything that jumps into the middle
+        let mut skipped = 0;
+        for (&addr, instruction) in &analysis.instructions {
+            if addr < skipped {
+                continue;
+            }
+            let mut end = addr;
+            while analysis.skippable(&optimized, end) {
+                end += analysis.instructions[&end].len();
+            }
+            if end == addr {
+                continue;
+            }
+            let destination = analysis.destination(&optimized, end);
+            if destination != addr + instruction.len() {
+                let target = Operand::immediate(destination);
+                store(&mut optimized, addr, &jump(addr, true, target));
+            }
+            skipped = end;
+        }
+        // threaded against the simplified jumps, which can only be shorter
+        for &addr in analysis.instructions.keys() {
+            if analysis.fixed(addr) {
+                if let Some(cells) = analysis.thread(&optimized, addr) {
+                    store(&mut optimized, addr, &cells);
+                }
+            }
+        }
+
+        let rewrites = analysis
+            .instructions
+            .values()
+            .filter(|instruction| {
+                (0..instruction.len()).any(|i| {
+                    program.peek(instruction.addr + i) != optimized.peek(instruction.addr + i)
+                })
+            })
+            .map(|instruction| Rewrite {
+                addr: instruction.addr,
+                before: disassemble_one(program, instruction.addr).unwrap().0,
+                after: disassemble_one(&optimized, instruction.addr).unwrap().0,
+            })
+            .collect();
+        (optimized, rewrites)
+    }
+}
+
+fn store(memory: &mut Memory, addr: Word, cells: &[Word]) {
+    for (i, cell) in cells.iter().enumerate() {
+        memory.write(addr + i as Word, *cell);
+    }
+}
+
+#[derive(Clone)]
+struct Instruction {
+    addr: Word,
+    opcode: Opcode,
+    params: Vec<Word>,
+}
+
+impl Instruction {
+    fn decode(memory: &Memory, addr: Word) -> Option<Instruction> {
+        if !memory.initialized(addr) {
+            return None;
+        }
+        let opcode = Opcode::new(memory.peek(addr))?;
+        let params = (0..opcode.modes().len() as Word)
+            .map(|i| memory.peek(addr + 1 + i))
+            .collect();
+        Some(Instruction {
+            addr,
+            opcode,
+            params,
+        })
+    }
+
+    fn len(&self) -> Word {
+        1 + self.params.len() as Word
+    }
+
+    // the operand written to, if any
+    fn output(&self) -> Option<usize> {
+        match self.opcode {
+            Opcode::Add(..) | Opcode::Mult(..) | Opcode::LT(..) | Opcode::Eq(..) => Some(2),
+            Opcode::Input(_) => Some(0),
+            _ => None,
+        }
+    }
+
+    fn operands(&self) -> Vec<Operand> {
+        self.opcode
+            .modes()
+            .into_iter()
+            .zip(&self.params)
+            .map(|(mode, value)| Operand {
+                mode: mode.clone(),
+                value: *value,
+            })
+            .collect()
+    }
+
+    // An address this might send execution to: its target if it's a jump,
+    // or the value it stores if that's a constant, which is how return
+    // addresses get pushed.
+    fn entry(&self) -> Option<Word> {
+        let operands = self.operands();
+        match (
+            &self.opcode,
+            operands.first().and_then(Operand::constant),
+            operands.get(1).and_then(Operand::constant),
+        ) {
+            (Opcode::Add(..), Some(a), Some(b)) => a.checked_add(b),
+            (Opcode::Mult(..), Some(a), Some(b)) => a.checked_mul(b),
+            _ => self.jump_target(),
+        }
+    }
+
+    // a jump to somewhere only known at run time
+    fn computed_jump(&self) -> bool {
+        matches!(self.opcode, Opcode::JIT(..) | Opcode::JIF(..)) && self.jump_target().is_none()
+    }
+
+    fn jump_target(&self) -> Option<Word> {
+        match (&self.opcode, self.params.get(1)) {
+            (Opcode::JIT(_, Mode::Immediate), Some(target))
+            | (Opcode::JIF(_, Mode::Immediate), Some(target)) => Some(*target),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Operand {
+    mode: Mode,
+    value: Word,
+}
+
+impl Operand {
+    fn immediate(value: Word) -> Operand {
+        Operand {
+            mode: Mode::Immediate,
+            value,
+        }
+    }
+
+    fn constant(&self) -> Option<Word> {
+        match self.mode {
+            Mode::Immediate => Some(self.value),
+            _ => None,
+        }
+    }
+
+    // whether leaving out the read can't be noticed: a bad address would trap
+    fn droppable(&self) -> bool {
+        match self.mode {
+            Mode::Position => self.value >= 0,
+            Mode::Immediate => true,
+            Mode::Relative => false,
+        }
+    }
+}
+
+// assembles an instruction back into cells
+fn encode(opcode: Word, operands: &[Operand]) -> Vec<Word> {
+    let mut instruction = opcode;
+    let mut place = 100;
+    for operand in operands {
+        let mode = match operand.mode {
+            Mode::Position => 0,
+            Mode::Immediate => 1,
+            Mode::Relative => 2,
+        };
+        instruction += mode * place;
+        place *= 10;
+    }
+    let mut cells = vec![instruction];
+    cells.extend(operands.iter().map(|operand| operand.value));
+    cells
+}
+
+fn copy(value: Operand, to: Operand) -> Vec<Word> {
+    encode(1, &[value, Operand::immediate(0), to])
+}
+
+fn jump(addr: Word, taken: bool, target: Operand) -> Vec<Word> {
+    match taken {
+        true => encode(5, &[Operand::immediate(1), target]),
+        // the target is never used, pointing it at the next instruction says so
+        false => encode(5, &[Operand::immediate(0), Operand::immediate(addr + 3)]),
+    }
+}
+
+/// Addresses an instruction may access. `computed` means some address isn't
+/// known until run time, and might be anywhere in the data ranges.
+#[derive(Default)]
+struct Cells {
+    known: HashSet<Word>,
+    computed: bool,
+}
+
+struct Analysis<'a> {
+    program: &'a Memory,
+    optimizer: &'a Optimizer,
+    // everything reachable, by address
+    instructions: BTreeMap<Word, Instruction>,
+    // whether any of them jumps to a computed address
+    computed_jumps: bool,
+    written: Cells,
+    read: Cells,
+    // how many reachable instructions each cell is part of
+    overlaps: HashMap<Word, usize>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(program: &'a Memory, optimizer: &'a Optimizer) -> Analysis<'a> {
+        let mut analysis = Analysis {
+            program,
+            optimizer,
+            instructions: BTreeMap::new(),
+            computed_jumps: false,
+            written: Cells::default(),
+            read: Cells::default(),
+            overlaps: HashMap::new(),
+        };
+        analysis.explore();
+        for instruction in analysis.instructions.values() {
+            for cell in instruction.addr..instruction.addr + instruction.len() {
+                *analysis.overlaps.entry(cell).or_insert(0) += 1;
+            }
+        }
+        analysis.accesses();
+        analysis
+    }
+
+    // Follows execution from 0. Computed jumps are followed to an immediate
+    // jump target or a constant some instruction stores, which only covers
+    // everywhere they go with `assume_returns`.
+    fn explore(&mut self) {
+        let data = &self.optimizer.data;
+        let mut queue = vec![0];
+        while let Some(addr) = queue.pop() {
+            if self.instructions.contains_key(&addr)
+                || data.iter().any(|range| range.contains(&addr))
+            {
+                continue;
+            }
+            let instruction = match Instruction::decode(self.program, addr) {
+                Some(instruction) => instruction,
+                None => continue,
+            };
+            queue.extend(instruction.entry());
+            if instruction.computed_jump() {
+                self.computed_jumps = true;
+            }
+            let operands = instruction.operands();
+            let falls_through = match instruction.opcode {
+                Opcode::Halt => false,
+                Opcode::JIT(..) => !matches!(operands[0].constant(), Some(c) if c != 0),
+                Opcode::JIF(..) => operands[0].constant() != Some(0),
+                _ => true,
+            };
+            if falls_through {
+                queue.push(addr + instruction.len());
+            }
+            self.instructions.insert(addr, instruction);
+        }
+    }
+
+    fn accesses(&mut self) {
+        // a write to an operand changes where that operand points, so writes
+        // are found by iterating until nothing new turns up
+        loop {
+            let mut written = Cells::default();
+            let mut read = Cells::default();
+            for instruction in self.instructions.values() {
+                let output = instruction.output();
+                for (i, operand) in instruction.operands().iter().enumerate() {
+                    let cells = if output == Some(i) {
+                        &mut written
+                    } else {
+                        &mut read
+                    };
+                    self.access(instruction.addr + 1 + i as Word, operand, cells);
+                }
+            }
+            let done = written.known.len() == self.written.known.len()
+                && written.computed == self.written.computed;
+            self.written = written;
+            self.read = read;
+            if done {
+                return;
+            }
+        }
+    }
+
+    fn access(&self, cell: Word, operand: &Operand, cells: &mut Cells) {
+        match operand.mode {
+            Mode::Position => {
+                cells.known.insert(operand.value);
+                if self.written(cell) {
+                    cells.computed = true;
+                }
+            }
+            Mode::Immediate => (),
+            Mode::Relative => cells.computed = true,
+        }
+    }
+
+    fn written(&self, addr: Word) -> bool {
+        self.written.known.contains(&addr) || (self.written.computed && self.in_data(addr))
+    }
+
+    fn read(&self, addr: Word) -> bool {
+        self.read.known.contains(&addr) || (self.read.computed && self.in_data(addr))
+    }
+
+    fn in_data(&self, addr: Word) -> bool {
+        let data = &self.optimizer.data;
+        data.is_empty() || data.iter().any(|range| range.contains(&addr))
+    }
+
+    /// Whether the instruction at `addr` is only ever executed, never written
+    /// or read as data, and doesn't overlap another reachable instruction.
+    fn fixed(&self, addr: Word) -> bool {
+        if self.computed_jumps && !self.optimizer.assume_returns {
+            // any of it could be jumped into
+            return false;
+        }
+        match self.instructions.get(&addr) {
+            Some(instruction) => (addr..addr + instruction.len()).all(|cell| {
+                !self.written(cell) && !self.read(cell) && self.overlaps.get(&cell) == Some(&1)
+            }),
+            None => false,
+        }
+    }
+
+    // a position operand that always reads the same value becomes that value
+    fn fold(&self, operand: Operand) -> Operand {
+        match operand.mode {
+            Mode::Position if operand.value >= 0 && !self.written(operand.value) => {
+                Operand::immediate(self.program.peek(operand.value))
+            }
+            _ => operand,
+        }
+    }
+
+    fn simplify(&self, instruction: &Instruction) -> Option<Vec<Word>> {
+        let addr = instruction.addr;
+        let mut operands = instruction.operands();
+        if let Some(output) = instruction.output() {
+            if operands[output].mode == Mode::Immediate {
+                // traps when it runs, leave it be
+                return None;
+            }
+        }
+        for (i, operand) in operands.iter_mut().enumerate() {
+            if instruction.output() != Some(i) {
+                *operand = self.fold(operand.clone());
+            }
+        }
+        let [a, b] = match &operands[..] {
+            [a, b, ..] => [a.clone(), b.clone()],
+            [a] => [a.clone(), Operand::immediate(0)],
+            [] => return None,
+        };
+        let to = operands.last().unwrap().clone();
+        let cells = match instruction.opcode {
+            Opcode::Add(..) => match (a.constant(), b.constant()) {
+                (Some(x), Some(y)) => copy(Operand::immediate(x.checked_add(y)?), to),
+                (Some(0), None) => copy(b, to),
+                (None, Some(0)) => copy(a, to),
+                _ => encode(1, &operands),
+            },
+            Opcode::Mult(..) => match (a.constant(), b.constant()) {
+                (Some(x), Some(y)) => copy(Operand::immediate(x.checked_mul(y)?), to),
+                (Some(0), None) if b.droppable() => copy(a, to),
+                (None, Some(0)) if a.droppable() => copy(b, to),
+                (Some(1), None) => copy(b, to),
+                (None, Some(1)) => copy(a, to),
+                _ => encode(2, &operands),
+            },
+            Opcode::LT(..) | Opcode::Eq(..) => match (a.constant(), b.constant()) {
+                (Some(x), Some(y)) => {
+                    let result = match instruction.opcode {
+                        Opcode::LT(..) => x < y,
+                        _ => x == y,
+                    };
+                    copy(Operand::immediate(result as Word), to)
+                }
+                _ if matches!(instruction.opcode, Opcode::LT(..)) => encode(7, &operands),
+                _ => encode(8, &operands),
+            },
+            Opcode::JIT(..) | Opcode::JIF(..) => {
+                let jit = matches!(instruction.opcode, Opcode::JIT(..));
+                match (a.constant(), b.constant()) {
+                    (_, Some(target)) if target == addr + 3 && a.droppable() => {
+                        jump(addr, false, b)
+                    }
+                    (Some(condition), _) if (condition != 0) == jit => jump(addr, true, b),
+                    // the target is read even when the jump isn't taken
+                    (Some(_), _) if b.droppable() => jump(addr, false, b),
+                    _ => encode(if jit { 5 } else { 6 }, &operands),
+                }
+            }
+            Opcode::Output(_) => encode(4, &operands),
+            Opcode::ARB(_) => encode(9, &operands),
+            Opcode::Input(_) | Opcode::Halt => return None,
+        };
+        Some(cells)
+    }
+
+    // where a jump to `target` really ends up, following unconditional jumps
+    // and skipping what makes no difference, none of which can change under us
+    fn destination(&self, optimized: &Memory, mut target: Word) -> Word {
+        let mut seen = HashSet::new();
+        while seen.insert(target) {
+            let next = match Instruction::decode(optimized, target) {
+                Some(instruction) if self.fixed(target) => {
+                    match (&instruction.opcode, &instruction.params[..]) {
+                        (Opcode::JIT(Mode::Immediate, Mode::Immediate), [1, next]) => *next,
+                        _ if self.skippable(optimized, target) => target + instruction.len(),
+                        _ => return target,
+                    }
+                }
+                _ => return target,
+            };
+            target = next;
+        }
+        // a loop of jumps, leave it to spin where it was
+        target
+    }
+
+    /// Whether running the instruction at `addr` makes no difference: it's a
+    /// jump that's never taken, or a store that's always overwritten before
+    /// anything reads it.
+    fn skippable(&self, optimized: &Memory, addr: Word) -> bool {
+        let instruction = match Instruction::decode(optimized, addr) {
+            Some(instruction) if self.fixed(addr) => instruction,
+            _ => return false,
+        };
+        let operands = instruction.operands();
+        match instruction.opcode {
+            Opcode::JIT(Mode::Immediate, Mode::Immediate) => instruction.params[0] == 0,
+            Opcode::Add(..) | Opcode::Mult(..) | Opcode::LT(..) | Opcode::Eq(..) => {
+                !self.may_trap(&instruction)
+                    && self.overwritten(optimized, operands[2].value, addr + instruction.len())
+            }
+            _ => false,
+        }
+    }
+
+    // Whether running from `addr` writes `cell` before reading it, without
+    // jumping, halting or trapping first. Only position operands are allowed
+    // on the way, so nothing reads it through a computed address.
+    fn overwritten(&self, optimized: &Memory, cell: Word, mut addr: Word) -> bool {
+        loop {
+            let instruction = match Instruction::decode(optimized, addr) {
+                Some(instruction) if self.fixed(addr) => instruction,
+                _ => return false,
+            };
+            if self.may_trap(&instruction) {
+                return false;
+            }
+            let output = instruction.output();
+            for (i, operand) in instruction.operands().iter().enumerate() {
+                if operand.mode == Mode::Position && operand.value == cell {
+                    return output == Some(i);
+                }
+            }
+            match (&instruction.opcode, &instruction.params[..]) {
+                (Opcode::JIT(Mode::Immediate, Mode::Immediate), [0, _]) => (),
+                (Opcode::JIT(..), _) | (Opcode::JIF(..), _) | (Opcode::Halt, _) => return false,
+                _ => (),
+            }
+            addr += instruction.len();
+        }
+    }
+
+    // whether running the instruction could stop the machine
+    fn may_trap(&self, instruction: &Instruction) -> bool {
+        let operands = instruction.operands();
+        if !operands.iter().all(Operand::droppable) {
+            return true;
+        }
+        if let Some(output) = instruction.output() {
+            if operands[output].mode == Mode::Immediate {
+                return true;
+            }
+        }
+        let constants = match &operands[..] {
+            [a, b, ..] => a.constant().zip(b.constant()),
+            _ => None,
+        };
+        let overflows = match (&instruction.opcode, constants) {
+            (Opcode::Add(..), Some((a, b))) => a.checked_add(b).is_none(),
+            (Opcode::Mult(..), Some((a, b))) => a.checked_mul(b).is_none(),
+            (Opcode::Add(..), None) | (Opcode::Mult(..), None) => true,
+            _ => false,
+        };
+        overflows && self.optimizer.overflow == Overflow::Trap
+    }
+
+    fn thread(&self, optimized: &Memory, addr: Word) -> Option<Vec<Word>> {
+        let instruction = Instruction::decode(optimized, addr)?;
+        let target = instruction.jump_target()?;
+        if let (Opcode::JIT(Mode::Immediate, _), [0, _]) =
+            (&instruction.opcode, &instruction.params[..])
+        {
+            return None;
+        }
+        let destination = self.destination(optimized, target);
+        if destination == target {
+            return None;
+        }
+        let mut cells = vec![optimized.peek(addr), instruction.params[0]];
+        cells.push(destination);
+        Some(cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Computer, Recorder, Session};
+    use super::*;
+    use crate::day11;
+
+    fn optimize(program: &str, data: Vec<Range<Word>>) -> Vec<String> {
+        let optimizer = Optimizer {
+            data,
+            ..Optimizer::new()
+        };
+        let (_, rewrites) = optimizer.optimize(&Memory::load(program));
+        rewrites
+            .iter()
+            .map(|rewrite| rewrite.after.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_copies() {
+        // mul by 1, add 0 from a cell that's never written, and a constant
+        // comparison; 21 is written by the input so it can't be folded
+        assert_eq!(
+            vec!["add [21], 0, [23]", "add 9, 0, [23]", "add 1, 0, [23]"],
+            optimize(
+                "3,21,1002,21,1,23,4,23,1001,22,0,23,4,23,1107,3,8,23,4,23,99,0,9,0",
+                vec![Range {
+                    start: 21,
+                    end: Word::MAX
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn test_written_cells_are_left_alone() {
+        // 12 is read by the first instruction but the second overwrites it
+        assert_eq!(
+            0,
+            optimize("1,12,12,13,3,12,4,13,99,0,0,0,5,0", vec![]).len()
+        );
+        // relative writes could go anywhere
+        assert_eq!(
+            0,
+            optimize("1,12,12,13,21101,1,1,0,4,13,99,0,5,0", vec![]).len()
+        );
+    }
+
+    #[test]
+    fn test_jumps() {
+        // a jump to the next instruction, which goes on to a constant
+        // condition, both threaded through the jump at 11, which only goes
+        // to the next instruction too
+        assert_eq!(
+            vec!["jt 1, 14", "jt 1, 14", "jt 0, 14"],
+            optimize(
+                "3,20,1005,20,5,1106,0,11,104,0,99,1105,1,14,104,1,99,0,0,0,0",
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn test_computed_jumps() {
+        // the jump at 4 goes wherever 11 says, which could be anywhere
+        let program = "1002,9,1,10,105,1,11,99,0,5,0,7";
+        assert_eq!(0, optimize(program, vec![]).len());
+        let optimizer = Optimizer {
+            assume_returns: true,
+            ..Optimizer::new()
+        };
+        let (_, rewrites) = optimizer.optimize(&Memory::load(program));
+        assert_eq!("add 5, 0, [10]", rewrites[0].after);
+    }
+
+    #[test]
+    fn test_skipped() {
+        // the copies to 20 at 2 and 6 are overwritten by the next one, so 2
+        // jumps past both; the last is left for the halt to see, and the
+        // jump at 14 is never taken
+        assert_eq!(
+            vec!["jt 1, 10", "add 9, 0, [20]", "add 1, 0, [20]", "jt 0, 17"],
+            optimize(
+                "3,18,1002,18,1,20,1001,19,0,20,1107,3,8,20,1106,1,17,99,0,9,0",
+                vec![]
+            )
+        );
+        // products nothing reads, unless they might trap
+        let optimizer = Optimizer {
+            overflow: Overflow::Trap,
+            ..Optimizer::new()
+        };
+        let program = Memory::load("3,17,1002,17,3,18,1002,17,5,18,1101,0,0,18,4,18,99,0,0");
+        assert_eq!(1, Optimizer::new().optimize(&program).1.len());
+        assert_eq!(0, optimizer.optimize(&program).1.len());
+    }
+
+    // runs to the end with `input` as every input
+    fn record(program: Memory, input: Word) -> Session<Word> {
+        let mut recorder = Recorder::new(Computer::new(program));
+        recorder.run().unwrap();
+        while !recorder.computer.halted() {
+            recorder.run_with_input(input).unwrap();
+        }
+        recorder.session
+    }
+
+    // how many instructions the program runs, with `input` as every input
+    fn steps(program: Memory, input: Word) -> u64 {
+        let mut computer = Computer::new(program);
+        let mut steps = 0;
+        while !computer.halted() {
+            if computer.step().unwrap() {
+                steps += 1;
+            } else if !computer.halted() {
+                computer.input(input);
+            }
+        }
+        steps
+    }
+
+    #[test]
+    fn test_fewer_steps() {
+        // the jump at 0 goes to another jump, so it's threaded past it
+        let program = Memory::load("1105,1,4,99,1105,1,7,104,5,99");
+        let (optimized, _) = Optimizer::new().optimize(&program);
+        assert_eq!((3, 2), (steps(program, 0), steps(optimized, 0)));
+        // two stores nothing reads are jumped over
+        let program = Memory::load("3,17,1002,17,2,18,1001,17,1,18,1008,17,5,18,4,18,99,0,0");
+        let (optimized, _) = Optimizer::new().optimize(&program);
+        assert_eq!((5, 4), (steps(program, 0), steps(optimized, 0)));
+    }
+
+    #[test]
+    fn test_day11() {
+        let program: Memory = Memory::load_file("data/day11.txt").unwrap();
+        // it modifies itself and jumps to return addresses
+        assert_eq!(0, Optimizer::new().optimize(&program).1.len());
+
+        // a little array indexed through the operand at 474, and the stack
+        // right after the program
+        let optimizer = Optimizer {
+            data: vec![458..463, program.len()..Word::MAX],
+            assume_returns: true,
+            ..Optimizer::new()
+        };
+        let (optimized, rewrites) = optimizer.optimize(&program);
+        assert!(!rewrites.is_empty());
+        // its main loop is full of stores to 10 that nothing reads
+        assert_eq!(
+            (92975, 83678),
+            (steps(program.clone(), 0), steps(optimized.clone(), 0))
+        );
+        for color in 0..2 {
+            assert_eq!(
+                day11::paint_panels(&program.to_string(), color),
+                day11::paint_panels(&optimized.to_string(), color)
+            );
+        }
+    }
+
+    #[test]
+    fn test_day13() {
+        let program: Memory = Memory::load_file("data/day13.txt").unwrap();
+        assert_eq!(0, Optimizer::new().optimize(&program).1.len());
+
+        // the tile map starts at 639, with the stack after it
+        let optimizer = Optimizer {
+            data: vec![Range {
+                start: 639,
+                end: Word::MAX,
+            }],
+            assume_returns: true,
+            ..Optimizer::new()
+        };
+        let (optimized, rewrites) = optimizer.optimize(&program);
+        assert!(!rewrites.is_empty());
+        let session = record(program.clone(), 0);
+        let computer = session.replay(optimized.clone(), None).unwrap();
+        assert!(computer.halted());
+        // every store in its loops is read, so there's nothing to skip
+        assert_eq!(steps(program, 0), steps(optimized, 0));
+    }
+}
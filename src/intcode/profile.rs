@@ -0,0 +1,73 @@
+use super::{Mode, Opcode};
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+/// The instruction set as it stood after each Intcode puzzle.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Isa {
+    /// add, mult and halt, position mode only
+    Day2,
+    /// adds input, output, jumps, comparisons and immediate mode
+    Day5,
+    /// adds relative mode and adjusting the relative base
+    Day9,
+}
+
+impl Isa {
+    pub(super) fn accepts(self, opcode: &Opcode) -> bool {
+        let op_ok = match opcode {
+            Opcode::Add(..) | Opcode::Mult(..) | Opcode::Halt => true,
+            Opcode::ARB(_) => self >= Isa::Day9,
+            _ => self >= Isa::Day5,
+        };
+        op_ok
+            && opcode.modes().iter().all(|mode| match mode {
+                Mode::Position => true,
+                Mode::Immediate => self >= Isa::Day5,
+                Mode::Relative => self >= Isa::Day9,
+            })
+    }
+}
+
+/// What a read from an address the program never wrote returns.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Uninitialized {
+    Zero,
+    Trap,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Profile {
+    pub isa: Isa,
+    pub uninitialized: Uninitialized,
+}
+
+impl Profile {
+    /// Only what the given day's puzzle allowed. Memory past the end of the
+    /// program wasn't specified until day 9, so earlier days trap on it.
+    pub fn strict(isa: Isa) -> Profile {
+        let uninitialized = if isa >= Isa::Day9 {
+            Uninitialized::Zero
+        } else {
+            Uninitialized::Trap
+        };
+        Profile { isa, uninitialized }
+    }
+
+    /// Everything we know how to run.
+    pub fn lenient() -> Profile {
+        Profile {
+            isa: Isa::Day9,
+            uninitialized: Uninitialized::Zero,
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Profile {
+        Profile::lenient()
+    }
+}
@@ -0,0 +1,338 @@
+use super::{Computer, Memory, Trap, Value};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Something observable that happened during a run. `step` counts executed
+/// instructions, so an input's step is that of the instruction that read it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Event<W> {
+    Input { step: u64, value: W },
+    Output { step: u64, value: W },
+}
+
+impl<W: Value> fmt::Display for Event<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Input { step, value } => write!(f, "in {} {}", step, value),
+            Event::Output { step, value } => write!(f, "out {} {}", step, value),
+        }
+    }
+}
+
+/// Every input and output of a run, in order.
+///
+/// Saved as text, one event per line, e.g. `in 1234 -1` or `out 1240 5`.
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Session<W> {
+    pub events: Vec<Event<W>>,
+}
+
+/// Where a replay stopped matching its session. `None` on the `expected`
+/// side means the program did more than was recorded; on the `actual` side,
+/// that it halted or stopped before doing what was recorded.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Divergence<W> {
+    pub index: usize,
+    pub expected: Option<Event<W>>,
+    pub actual: Option<Event<W>>,
+}
+
+impl<W: Value> fmt::Display for Divergence<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |event: &Option<Event<W>>| match event {
+            Some(event) => event.to_string(),
+            None => "nothing".to_string(),
+        };
+        write!(
+            f,
+            "event {}: expected {}, got {}",
+            self.index,
+            show(&self.expected),
+            show(&self.actual)
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError<W> {
+    Diverged(Divergence<W>),
+    Trapped(Trap),
+}
+
+impl<W: Value> fmt::Display for ReplayError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Diverged(divergence) => write!(f, "diverged at {}", divergence),
+            ReplayError::Trapped(trap) => write!(f, "{}", trap),
+        }
+    }
+}
+
+impl<W: Value> Session<W> {
+    pub fn new() -> Session<W> {
+        Session { events: vec![] }
+    }
+
+    pub fn parse(text: &str) -> Result<Session<W>, String> {
+        let mut events = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad = || format!("line {}: can't parse {:?}", i + 1, line);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() != 3 {
+                return Err(bad());
+            }
+            let step = parts[1].parse().map_err(|_| bad())?;
+            let value = parts[2].parse().map_err(|_| bad())?;
+            events.push(match parts[0] {
+                "in" => Event::Input { step, value },
+                "out" => Event::Output { step, value },
+                _ => return Err(bad()),
+            });
+        }
+        Ok(Session { events })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Session<W>> {
+        let text = fs::read_to_string(path)?;
+        Session::parse(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Runs `program`, feeding it the recorded inputs and checking its
+    /// outputs against the recorded ones. If `until` is given, stops once
+    /// that many instructions have run, leaving the machine in the state it
+    /// was in at that point of the original session.
+    pub fn replay(
+        &self,
+        program: Memory<W>,
+        until: Option<u64>,
+    ) -> Result<Computer<W>, ReplayError<W>> {
+        let mut computer = Computer::new(program);
+        let mut expected = self.events.iter().enumerate();
+        let mut steps = 0;
+        loop {
+            if until == Some(steps) {
+                return Ok(computer);
+            }
+            let progressed = computer.step().map_err(ReplayError::Trapped)?;
+            if progressed {
+                steps += 1;
+            }
+            for value in computer.outputs.drain(..) {
+                let actual = Event::Output {
+                    step: steps.saturating_sub(1),
+                    value,
+                };
+                match expected.next() {
+                    Some((_, event)) if *event == actual => (),
+                    Some((i, event)) => return diverged(i, Some(event), Some(actual)),
+                    None => return diverged(self.events.len(), None, Some(actual)),
+                }
+            }
+            if progressed {
+                continue;
+            }
+
+            match expected.next() {
+                // recorded inputs are consumed by the instruction that's waiting
+                Some((_, Event::Input { step, value })) if *step == steps && !computer.halted() => {
+                    computer.input(value.clone())
+                }
+                Some((i, event)) => return diverged(i, Some(event), None),
+                // the recording ended here too
+                None => return Ok(computer),
+            }
+        }
+    }
+}
+
+fn diverged<W: Value, T>(
+    index: usize,
+    expected: Option<&Event<W>>,
+    actual: Option<Event<W>>,
+) -> Result<T, ReplayError<W>> {
+    Err(ReplayError::Diverged(Divergence {
+        index,
+        expected: expected.cloned(),
+        actual,
+    }))
+}
+
+/// Drives a `Computer` like `run_with_input` does, writing down every input
+/// and output so the session can be saved and replayed.
+pub struct Recorder<W> {
+    pub computer: Computer<W>,
+    pub session: Session<W>,
+    pending: Option<W>,
+    steps: u64,
+}
+
+impl<W: Value> Recorder<W> {
+    /// Replays start from the program, so `computer` should be fresh.
+    /// Outputs it already has are recorded as if the first step made them.
+    pub fn new(computer: Computer<W>) -> Recorder<W> {
+        Recorder {
+            computer,
+            session: Session::new(),
+            pending: None,
+            steps: 0,
+        }
+    }
+
+    /// Runs until the machine halts or wants input, returning what it output.
+    pub fn run(&mut self) -> Result<Vec<W>, Trap> {
+        let mut outputs = vec![];
+        loop {
+            let progressed = self.computer.step()?;
+            if progressed {
+                self.steps += 1;
+                if self.computer.input.is_none() {
+                    if let Some(value) = self.pending.take() {
+                        self.session.events.push(Event::Input {
+                            step: self.steps.saturating_sub(1),
+                            value,
+                        });
+                    }
+                }
+            }
+            for value in self.computer.outputs.drain(..) {
+                self.session.events.push(Event::Output {
+                    step: self.steps.saturating_sub(1),
+                    value: value.clone(),
+                });
+                outputs.push(value);
+            }
+            if !progressed {
+                return Ok(outputs);
+            }
+        }
+    }
+
+    pub fn run_with_input(&mut self, input: W) -> Result<Vec<W>, Trap> {
+        self.computer.input(input.clone());
+        self.pending = Some(input);
+        self.run()
+    }
+}
+
+impl<W: Value> Default for Session<W> {
+    fn default() -> Session<W> {
+        Session::new()
+    }
+}
+
+impl<W: Value> fmt::Display for Session<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# intcode session")?;
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // adds up its inputs, printing the running total, until it reads a 0
+    const ADDER: &str = "3,100,1006,100,14,1,100,101,101,4,101,1105,1,0,99";
+
+    fn record(inputs: &[i64]) -> Session<i64> {
+        let mut recorder = Recorder::new(Computer::load(ADDER));
+        recorder.run().unwrap();
+        for input in inputs {
+            recorder.run_with_input(*input).unwrap();
+        }
+        recorder.session
+    }
+
+    #[test]
+    fn test_record() {
+        let session = record(&[3, 4, 0]);
+        assert_eq!(
+            "# intcode session\nin 0 3\nout 3 3\nin 5 4\nout 8 7\nin 10 0\n",
+            session.to_string()
+        );
+        assert_eq!(Ok(session.clone()), Session::parse(&session.to_string()));
+    }
+
+    #[test]
+    fn test_replay() {
+        let session = record(&[3, 4, 0]);
+        let computer = session.replay(Memory::load(ADDER), None).unwrap();
+        assert!(computer.halted());
+
+        // stop partway and look at the state then
+        let mut computer = session.replay(Memory::load(ADDER), Some(7)).unwrap();
+        assert_eq!(4, computer.memory.read(100));
+        assert_eq!(3, computer.memory.read(101));
+
+        // a recording that stops early replays up to where it stopped
+        let session = record(&[3]);
+        let computer = session.replay(Memory::load(ADDER), None).unwrap();
+        assert!(!computer.halted());
+    }
+
+    #[test]
+    fn test_divergence() {
+        let mut session = record(&[3, 4, 0]);
+        session.events[3] = Event::Output { step: 8, value: 8 };
+        match session.replay(Memory::load(ADDER), None) {
+            Err(ReplayError::Diverged(divergence)) => {
+                assert_eq!(3, divergence.index);
+                assert_eq!(
+                    "event 3: expected out 8 8, got out 8 7",
+                    divergence.to_string()
+                );
+            }
+            _ => panic!("expected a divergence"),
+        }
+
+        // the program changed so it halts right away
+        match session.replay(Memory::load("99"), None) {
+            Err(ReplayError::Diverged(divergence)) => {
+                assert_eq!(0, divergence.index);
+                assert_eq!(None, divergence.actual);
+            }
+            _ => panic!("expected a divergence"),
+        }
+    }
+
+    #[test]
+    fn test_used_machine() {
+        let mut computer = Computer::load(ADDER);
+        computer.outputs.push(9);
+        let mut recorder = Recorder::new(computer);
+        assert_eq!(vec![9], recorder.run().unwrap());
+        assert_eq!("# intcode session\nout 0 9\n", recorder.session.to_string());
+        // which a fresh machine doesn't do
+        assert!(recorder.session.replay(Memory::load(ADDER), None).is_err());
+    }
+
+    #[test]
+    fn test_day13_session() {
+        let program = fs::read_to_string("data/day13.txt").unwrap();
+        let mut recorder = Recorder::new(Computer::load(&program));
+        recorder.run().unwrap();
+        for joystick in &[0, 0, -1, 1, 1, 0, -1] {
+            recorder.run_with_input(*joystick).unwrap();
+        }
+        let session = recorder.session;
+        let computer = session.replay(Memory::load(&program), None).unwrap();
+        assert_eq!(
+            recorder.computer.memory.to_string(),
+            computer.memory.to_string()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use super::{Computer, Memory, Value, Word};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// One way of starting the program: cells to overwrite before it runs and
+/// inputs to feed it, in order, whenever it asks.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Candidate<W = Word> {
+    pub patches: Vec<(Word, W)>,
+    pub inputs: Vec<W>,
+}
+
+impl<W: Value> Candidate<W> {
+    pub fn patches(patches: Vec<(Word, W)>) -> Candidate<W> {
+        Candidate {
+            patches,
+            inputs: vec![],
+        }
+    }
+
+    pub fn inputs(inputs: Vec<W>) -> Candidate<W> {
+        Candidate {
+            patches: vec![],
+            inputs,
+        }
+    }
+
+    /// Runs the candidate until it halts, traps, runs out of input or goes
+    /// over `max_steps`. Only a halted machine is returned. Panics if the
+    /// program has devices mapped, which every run would share.
+    pub fn run(&self, program: &Memory<W>, max_steps: Option<u64>) -> Option<Computer<W>> {
+        assert_no_devices(program);
+        let mut computer = Computer::new(program.clone());
+        for (addr, value) in &self.patches {
+            computer.memory.write(*addr, value.clone());
+        }
+        let mut inputs = self.inputs.iter();
+        let mut steps = 0;
+        loop {
+            if max_steps == Some(steps) {
+                return None;
+            }
+            match computer.step() {
+                Ok(true) => steps += 1,
+                Ok(false) if computer.halted() => return Some(computer),
+                Ok(false) => computer.input(inputs.next()?.clone()),
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+// copies of a memory share its devices, so candidates wouldn't run apart
+fn assert_no_devices<W: Value>(program: &Memory<W>) {
+    assert!(
+        !program.has_devices(),
+        "can't search a program with devices mapped"
+    );
+}
+
+pub struct Search {
+    pub threads: usize,
+    /// How long a candidate may run before it's given up on. `None` waits
+    /// for every one to stop by itself, which a program that loops forever
+    /// never does.
+    pub max_steps: Option<u64>,
+}
+
+impl Search {
+    /// What `Search::default()` allows each candidate.
+    pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+}
+
+impl Default for Search {
+    fn default() -> Search {
+        Search {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            max_steps: Some(Search::DEFAULT_MAX_STEPS),
+        }
+    }
+}
+
+impl Search {
+    /// The earliest candidate in `candidates` whose final state satisfies
+    /// `predicate`. Workers stop picking up new candidates once something
+    /// earlier has matched, so the answer is the same however many threads run.
+    pub fn first<W, F>(
+        &self,
+        program: &Memory<W>,
+        candidates: &[Candidate<W>],
+        predicate: F,
+    ) -> Option<Candidate<W>>
+    where
+        W: Value + Send + Sync,
+        F: Fn(&mut Computer<W>) -> bool + Sync,
+    {
+        let best = AtomicUsize::new(usize::MAX);
+        self.each_match(
+            program,
+            candidates,
+            &predicate,
+            |i| {
+                best.fetch_min(i, Ordering::SeqCst);
+                true
+            },
+            &best,
+        );
+        candidates.get(best.into_inner()).cloned()
+    }
+
+    /// Every candidate that satisfies `predicate`, in the order given.
+    pub fn all<W, F>(
+        &self,
+        program: &Memory<W>,
+        candidates: &[Candidate<W>],
+        predicate: F,
+    ) -> Vec<Candidate<W>>
+    where
+        W: Value + Send + Sync,
+        F: Fn(&mut Computer<W>) -> bool + Sync,
+    {
+        let matches = Mutex::new(vec![]);
+        let never = AtomicUsize::new(usize::MAX);
+        self.each_match(
+            program,
+            candidates,
+            &predicate,
+            |i| {
+                matches.lock().unwrap().push(i);
+                false
+            },
+            &never,
+        );
+        let mut matches = matches.into_inner().unwrap();
+        matches.sort_unstable();
+        matches.into_iter().map(|i| candidates[i].clone()).collect()
+    }
+
+    /// `f` of each of `items`, in order, worked out across the threads. For
+    /// searches that want more than a yes or no from each run, like the best
+    /// of all of them.
+    pub fn map<T, R, F>(&self, items: &[T], f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync,
+    {
+        let results = Mutex::new(vec![]);
+        let never = AtomicUsize::new(usize::MAX);
+        self.work(items.len(), &never, |i| {
+            let result = f(&items[i]);
+            results.lock().unwrap().push((i, result));
+            false
+        });
+        let mut results = results.into_inner().unwrap();
+        results.sort_unstable_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    // Runs candidates, reporting the index of each that matches to `on_match`.
+    fn each_match<W, F, M>(
+        &self,
+        program: &Memory<W>,
+        candidates: &[Candidate<W>],
+        predicate: &F,
+        on_match: M,
+        stop: &AtomicUsize,
+    ) where
+        W: Value + Send + Sync,
+        F: Fn(&mut Computer<W>) -> bool + Sync,
+        M: Fn(usize) -> bool + Sync,
+    {
+        assert_no_devices(program);
+        self.work(candidates.len(), stop, |i| {
+            match candidates[i].run(program, self.max_steps) {
+                Some(mut computer) => predicate(&mut computer) && on_match(i),
+                None => false,
+            }
+        });
+    }
+
+    // Hands out indexes below `count` in order until they pass `stop`. A
+    // thread quits once `job` returns true.
+    fn work<J: Fn(usize) -> bool + Sync>(&self, count: usize, stop: &AtomicUsize, job: J) {
+        let next = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..self.threads.max(1) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    if i >= count || i > stop.load(Ordering::SeqCst) || job(i) {
+                        return;
+                    }
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Timer;
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_first_is_earliest() {
+        // outputs its input, halts
+        let program: Memory = Memory::load("3,0,4,0,99");
+        let candidates: Vec<Candidate> =
+            (0..100).map(|i| Candidate::inputs(vec![i % 10])).collect();
+        for threads in 1..5 {
+            let search = Search {
+                threads,
+                max_steps: None,
+            };
+            let found = search.first(&program, &candidates, |c| c.outputs == vec![7]);
+            assert_eq!(Some(Candidate::inputs(vec![7])), found);
+        }
+    }
+
+    #[test]
+    fn test_all() {
+        let program: Memory = Memory::load("3,0,4,0,99");
+        let candidates: Vec<Candidate> = (0..30).map(|i| Candidate::inputs(vec![i])).collect();
+        let found = Search::default().all(&program, &candidates, |c| c.outputs[0] % 10 == 3);
+        assert_eq!(
+            vec![
+                Candidate::inputs(vec![3]),
+                Candidate::inputs(vec![13]),
+                Candidate::inputs(vec![23])
+            ],
+            found
+        );
+    }
+
+    #[test]
+    fn test_patches_and_step_limit() {
+        // jumps to itself forever unless cell 2 is patched
+        let program: Memory = Memory::load("1105,1,0,99");
+        let candidates = vec![
+            Candidate::patches(vec![(2, 0)]),
+            Candidate::patches(vec![(2, 3)]),
+        ];
+        let search = Search {
+            threads: 2,
+            max_steps: Some(1000),
+        };
+        let found = search.all(&program, &candidates, |c| c.halted());
+        assert_eq!(vec![Candidate::patches(vec![(2, 3)])], found);
+    }
+
+    #[test]
+    fn test_default_step_limit() {
+        // the second candidate loops forever, and is given up on
+        let program: Memory = Memory::load("3,9,1005,9,2,99,0,0,0,0");
+        let candidates = vec![Candidate::inputs(vec![0]), Candidate::inputs(vec![1])];
+        let found = Search::default().all(&program, &candidates, |c| c.halted());
+        assert_eq!(vec![Candidate::inputs(vec![0])], found);
+    }
+
+    #[test]
+    fn test_map() {
+        let squares = Search::default().map(&[1, 2, 3, 4, 5], |n| n * n);
+        assert_eq!(vec![1, 4, 9, 16, 25], squares);
+    }
+
+    #[test]
+    #[should_panic(expected = "devices mapped")]
+    fn test_devices_are_refused() {
+        let mut program: Memory = Memory::load("3,0,4,0,99");
+        program.map(10..11, Arc::new(Mutex::new(Timer::new())));
+        Search::default().all(&program, &[Candidate::inputs(vec![1])], |_| true);
+    }
+}
@@ -0,0 +1,222 @@
+use super::{disassemble_one, Computer, Memory, Opcode, Trap, Value, Word};
+use serde::de::DeserializeOwned;
+use serde::{ser, Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SerializeError {
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SerializeError::Json(error) => write!(f, "{}", error),
+            SerializeError::Binary(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// How `Memory` is written out: cells in address order, so the same state
+/// always serializes the same way.
+#[derive(Serialize, Deserialize)]
+pub(super) struct Image<W> {
+    cells: BTreeMap<Word, W>,
+}
+
+// devices can't be saved, and leaving them out would quietly change what
+// the program sees, so memory with any mapped doesn't serialize
+impl<W: Serialize + Clone> Serialize for Memory<W> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !self.devices.is_empty() {
+            return Err(ser::Error::custom(
+                "can't serialize memory with devices mapped",
+            ));
+        }
+        let image = Image {
+            cells: self
+                .mem
+                .iter()
+                .map(|(addr, value)| (*addr, value.clone()))
+                .collect(),
+        };
+        image.serialize(serializer)
+    }
+}
+
+impl<W> From<Image<W>> for Memory<W> {
+    fn from(image: Image<W>) -> Memory<W> {
+        Memory {
+            mem: image.cells.into_iter().collect(),
+            devices: vec![],
+        }
+    }
+}
+
+/// One executed instruction, as recorded by `Computer::trace`, both decoded
+/// and disassembled as it was when it ran.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub pc: Word,
+    pub relative_base: Word,
+    pub opcode: Opcode,
+    pub instruction: String,
+}
+
+impl<W: Value + Serialize + DeserializeOwned> Computer<W> {
+    /// Fails if the memory has devices mapped.
+    pub fn to_json(&self) -> Result<String, SerializeError> {
+        serde_json::to_string_pretty(self).map_err(SerializeError::Json)
+    }
+
+    pub fn from_json(text: &str) -> Result<Computer<W>, SerializeError> {
+        serde_json::from_str(text).map_err(SerializeError::Json)
+    }
+
+    /// Fails if the memory has devices mapped.
+    pub fn to_binary(&self) -> Result<Vec<u8>, SerializeError> {
+        bincode::serialize(self).map_err(SerializeError::Binary)
+    }
+
+    pub fn from_binary(bytes: &[u8]) -> Result<Computer<W>, SerializeError> {
+        bincode::deserialize(bytes).map_err(SerializeError::Binary)
+    }
+
+    /// Runs up to `steps` instructions, recording each one. Stops early if
+    /// the machine halts or waits for input.
+    pub fn trace(&mut self, steps: usize) -> Result<Vec<TraceStep>, Trap> {
+        let mut trace = vec![];
+        for _ in 0..steps {
+            let step = TraceStep {
+                pc: self.pc,
+                relative_base: self.relative_base,
+                opcode: match self.instruction() {
+                    Some(opcode) => opcode,
+                    None => break,
+                },
+                instruction: match disassemble_one(&self.memory, self.pc) {
+                    Some((text, _)) => text,
+                    None => break,
+                },
+            };
+            if !self.step()? {
+                if self.halted {
+                    trace.push(step);
+                }
+                break;
+            }
+            trace.push(step);
+        }
+        Ok(trace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Mode, Modifications, Overflow, Timer};
+    use super::*;
+    use num::BigInt;
+    use std::sync::{Arc, Mutex};
+
+    const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut computer = Computer::load(QUINE);
+        computer.trace(20).unwrap();
+        let restored: Computer = Computer::from_json(&computer.to_json().unwrap()).unwrap();
+        assert_eq!(computer.to_json().unwrap(), restored.to_json().unwrap());
+
+        // carries on from where it was saved
+        let mut restored = restored;
+        computer.run();
+        restored.run();
+        assert_eq!(computer.outputs, restored.outputs);
+        assert_eq!(computer.memory.to_string(), restored.memory.to_string());
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let mut computer = Computer::new(Memory::<BigInt>::parse(
+            "1102,4294967296,4294967296,7,4,7,99,0",
+        ));
+        computer.overflow = Overflow::Trap;
+        computer.run();
+        let restored: Computer<BigInt> =
+            Computer::from_binary(&computer.to_binary().unwrap()).unwrap();
+        assert_eq!(computer.outputs, restored.outputs);
+        assert_eq!(Overflow::Trap, restored.overflow);
+        assert!(restored.halted());
+        assert!(Computer::<BigInt>::from_binary(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_golden_state() {
+        // the quine after 20 steps; regenerate the file if the format
+        // changes on purpose
+        let mut computer = Computer::load(QUINE);
+        computer.trace(20).unwrap();
+        let golden = std::fs::read_to_string("data/golden/quine_20_steps.json").unwrap();
+        assert_eq!(golden.trim(), computer.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_modifications_roundtrip() {
+        // patches the add at 4 before it runs
+        let mut computer = Computer::load("1101,90,9,4,1,0,0,0,99");
+        computer.modifications = Some(Modifications::new());
+        computer.run();
+        let sites = computer.modifications.as_ref().unwrap().sites();
+        assert_eq!(1, sites.len());
+        let json: Computer = Computer::from_json(&computer.to_json().unwrap()).unwrap();
+        let binary: Computer = Computer::from_binary(&computer.to_binary().unwrap()).unwrap();
+        assert_eq!(sites, json.modifications.unwrap().sites());
+        assert_eq!(sites, binary.modifications.unwrap().sites());
+    }
+
+    #[test]
+    fn test_devices_fail() {
+        let mut computer = Computer::load("99");
+        computer
+            .memory
+            .map(10..11, Arc::new(Mutex::new(Timer::new())));
+        let error = computer.to_json().unwrap_err().to_string();
+        assert!(error.contains("devices mapped"), "{}", error);
+        assert!(computer.to_binary().is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut computer = Computer::load(QUINE);
+        let trace = computer.trace(3).unwrap();
+        assert_eq!(
+            TraceStep {
+                pc: 0,
+                relative_base: 0,
+                opcode: Opcode::ARB(Mode::Immediate),
+                instruction: "arb 1".to_string()
+            },
+            trace[0]
+        );
+        assert_eq!(
+            TraceStep {
+                pc: 2,
+                relative_base: 1,
+                opcode: Opcode::Output(Mode::Relative),
+                instruction: "out [rb-1]".to_string()
+            },
+            trace[1]
+        );
+        let json = serde_json::to_string(&trace).unwrap();
+        assert_eq!(
+            trace,
+            serde_json::from_str::<Vec<TraceStep>>(&json).unwrap()
+        );
+
+        // stops at the halt
+        let mut computer = Computer::load("104,1,99");
+        assert_eq!(2, computer.trace(10).unwrap().len());
+    }
+}
@@ -0,0 +1,84 @@
+use num::{BigInt, FromPrimitive, One, ToPrimitive, Zero};
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+/// What to do when `Add` or `Mult` produces a result the word type can't hold.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Overflow {
+    Wrap,
+    Trap,
+    Saturate,
+}
+
+/// A type that can be stored in a memory cell. Addresses, the pc and the
+/// relative base are always `Word`s, so a value has to convert to one before
+/// it can be used as a pointer or jump target.
+pub trait Value:
+    Clone + Debug + Display + Ord + FromStr + Zero + One + ToPrimitive + FromPrimitive
+{
+    /// Returns `None` when the result overflows and the policy is `Trap`.
+    fn add_with(&self, other: &Self, overflow: Overflow) -> Option<Self>;
+    fn mul_with(&self, other: &Self, overflow: Overflow) -> Option<Self>;
+}
+
+macro_rules! primitive_value {
+    ($($t:ty),*) => {
+        $(
+            impl Value for $t {
+                fn add_with(&self, other: &Self, overflow: Overflow) -> Option<Self> {
+                    match overflow {
+                        Overflow::Wrap => Some(self.wrapping_add(*other)),
+                        Overflow::Trap => self.checked_add(*other),
+                        Overflow::Saturate => Some(self.saturating_add(*other)),
+                    }
+                }
+
+                fn mul_with(&self, other: &Self, overflow: Overflow) -> Option<Self> {
+                    match overflow {
+                        Overflow::Wrap => Some(self.wrapping_mul(*other)),
+                        Overflow::Trap => self.checked_mul(*other),
+                        Overflow::Saturate => Some(self.saturating_mul(*other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+primitive_value!(i32, i64, i128);
+
+// bigints never overflow, so the policy doesn't matter
+impl Value for BigInt {
+    fn add_with(&self, other: &Self, _overflow: Overflow) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn mul_with(&self, other: &Self, _overflow: Overflow) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(Some(i32::MIN), i32::MAX.add_with(&1, Overflow::Wrap));
+        assert_eq!(None, i32::MAX.add_with(&1, Overflow::Trap));
+        assert_eq!(Some(i32::MAX), i32::MAX.add_with(&1, Overflow::Saturate));
+        assert_eq!(Some(i64::MAX), i64::MIN.mul_with(&-1, Overflow::Saturate));
+        assert_eq!(Some(6), 2i128.mul_with(&3, Overflow::Trap));
+    }
+
+    #[test]
+    fn test_bigint() {
+        let big = BigInt::from_i64(i64::MAX).unwrap();
+        let sum = big.add_with(&big, Overflow::Trap).unwrap();
+        assert_eq!("18446744073709551614", sum.to_string());
+        assert_eq!(None, sum.to_i64());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops;
+use std::ops::RangeInclusive;
+
+/// The digits of `n`, most significant first.
+pub fn digits(n: u64) -> Vec<u8> {
+    n.to_string().bytes().map(|b| b - b'0').collect()
+}
+
+type Predicate = dyn Fn(&[u8]) -> bool;
+
+/// Something a password's digits must satisfy. Rules combine with `and`,
+/// `or` and `!`.
+pub enum Rule {
+    /// Every digit is at least the one before it.
+    NonDecreasing,
+    /// Somewhere the same digit appears exactly this many times in a row.
+    RunOfExactly(usize),
+    /// Somewhere the same digit appears at least this many times in a row.
+    RunOfAtLeast(usize),
+    /// The digits add up to a number in the range.
+    DigitSum(RangeInclusive<u32>),
+    /// There are exactly this many digits.
+    Length(usize),
+    Custom(Box<Predicate>),
+    All(Vec<Rule>),
+    Any(Vec<Rule>),
+    Not(Box<Rule>),
+}
+
+// the lengths of the runs of equal digits, in order
+fn runs(digits: &[u8]) -> Vec<usize> {
+    let mut runs: Vec<usize> = vec![];
+    for (i, digit) in digits.iter().enumerate() {
+        match runs.last_mut() {
+            Some(run) if digits[i - 1] == *digit => *run += 1,
+            _ => runs.push(1),
+        }
+    }
+    runs
+}
+
+impl Rule {
+    pub fn custom(predicate: impl Fn(&[u8]) -> bool + 'static) -> Rule {
+        Rule::Custom(Box::new(predicate))
+    }
+
+    /// Both rules must hold.
+    pub fn and(self, other: Rule) -> Rule {
+        match self {
+            Rule::All(mut rules) => {
+                rules.push(other);
+                Rule::All(rules)
+            }
+            rule => Rule::All(vec![rule, other]),
+        }
+    }
+
+    /// Either rule must hold.
+    pub fn or(self, other: Rule) -> Rule {
+        match self {
+            Rule::Any(mut rules) => {
+                rules.push(other);
+                Rule::Any(rules)
+            }
+            rule => Rule::Any(vec![rule, other]),
+        }
+    }
+
+    pub fn matches(&self, digits: &[u8]) -> bool {
+        match self {
+            Rule::NonDecreasing => digits.windows(2).all(|pair| pair[0] <= pair[1]),
+            Rule::RunOfExactly(n) => runs(digits).contains(n),
+            Rule::RunOfAtLeast(n) => runs(digits).iter().any(|run| run >= n),
+            Rule::DigitSum(range) => {
+                range.contains(&digits.iter().map(|digit| u32::from(*digit)).sum())
+            }
+            Rule::Length(n) => digits.len() == *n,
+            Rule::Custom(predicate) => predicate(digits),
+            Rule::All(rules) => rules.iter().all(|rule| rule.matches(digits)),
+            Rule::Any(rules) => rules.iter().any(|rule| rule.matches(digits)),
+            Rule::Not(rule) => !rule.matches(digits),
+        }
+    }
+
+    /// Whether the password `n` follows the rule.
+    pub fn check(&self, n: u64) -> bool {
+        self.matches(&digits(n))
+    }
+
+    /// How many passwords in `range` follow the rule, trying each one.
+    pub fn count(&self, range: RangeInclusive<u64>) -> usize {
+        range.filter(|n| self.check(*n)).count()
+    }
+}
+
+impl ops::Not for Rule {
+    type Output = Rule;
+
+    fn not(self) -> Rule {
+        match self {
+            Rule::Not(rule) => *rule,
+            rule => Rule::Not(Box::new(rule)),
+        }
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::NonDecreasing => write!(f, "NonDecreasing"),
+            Rule::RunOfExactly(n) => write!(f, "RunOfExactly({})", n),
+            Rule::RunOfAtLeast(n) => write!(f, "RunOfAtLeast({})", n),
+            Rule::DigitSum(range) => write!(f, "DigitSum({:?})", range),
+            Rule::Length(n) => write!(f, "Length({})", n),
+            Rule::Custom(_) => write!(f, "Custom(..)"),
+            Rule::All(rules) => f.debug_tuple("All").field(rules).finish(),
+            Rule::Any(rules) => f.debug_tuple("Any").field(rules).finish(),
+            Rule::Not(rule) => f.debug_tuple("Not").field(rule).finish(),
+        }
+    }
+}
+
+/// The run of equal digits a password counted by `count_non_decreasing`
+/// must have.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Run {
+    AtLeast(usize),
+    Exactly(usize),
+}
+
+impl Run {
+    // whether a run of `len` that's just ended is the one wanted
+    fn wanted(self, len: usize) -> bool {
+        match self {
+            Run::AtLeast(n) => len >= n,
+            Run::Exactly(n) => len == n,
+        }
+    }
+
+    // runs longer than this all count the same
+    fn cap(self) -> usize {
+        match self {
+            Run::AtLeast(n) => n,
+            Run::Exactly(n) => n + 1,
+        }
+    }
+}
+
+/// How many numbers in `range` have digits that never decrease and a run
+/// of equal digits like `run`, the same as counting what
+/// `Rule::NonDecreasing.and(Rule::RunOfAtLeast(n))` or `RunOfExactly(n)`
+/// allows but without trying every number, so ranges of any width are
+/// quick.
+pub fn count_non_decreasing(range: RangeInclusive<u64>, run: Run) -> u64 {
+    let (start, end) = range.into_inner();
+    if start > end {
+        return 0;
+    }
+    let below = if start == 0 {
+        0
+    } else {
+        count_up_to(start - 1, run)
+    };
+    count_up_to(end, run) - below
+}
+
+// how many of 0 to `n` count
+fn count_up_to(n: u64, run: Run) -> u64 {
+    let bound = digits(n);
+    // 0 is a single digit, and has no others to be in order with
+    let zero = run.wanted(1) as u64;
+    let shorter: u64 = (1..bound.len())
+        .map(|len| count_digits(&vec![9; len], run))
+        .sum();
+    zero + shorter + count_digits(&bound, run)
+}
+
+// how many numbers with as many digits as `bound`, and no more than it,
+// count. Their digits never decrease, so none but 0 itself has a 0 in it.
+fn count_digits(bound: &[u8], run: Run) -> u64 {
+    // (last digit, length of its run so far, wanted run seen, still equal
+    // to the start of `bound`) and how many numbers get there
+    let mut states: HashMap<(u8, usize, bool, bool), u64> = HashMap::new();
+    for digit in 1..=bound[0] {
+        *states
+            .entry((digit, 1, false, digit == bound[0]))
+            .or_default() += 1;
+    }
+    for limit in &bound[1..] {
+        let mut next = HashMap::new();
+        for ((last, len, seen, tight), count) in states {
+            let highest = if tight { *limit } else { 9 };
+            for digit in last..=highest {
+                let tight = tight && digit == *limit;
+                let state = if digit == last {
+                    (digit, (len + 1).min(run.cap()), seen, tight)
+                } else {
+                    (digit, 1, seen || run.wanted(len), tight)
+                };
+                *next.entry(state).or_default() += count;
+            }
+        }
+        states = next;
+    }
+    states
+        .into_iter()
+        .filter(|((_, len, seen, _), _)| *seen || run.wanted(*len))
+        .map(|(_, count)| count)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(vec![1, 2, 0, 3], digits(1203));
+        assert_eq!(vec![0], digits(0));
+        assert_eq!(vec![2, 3, 2], runs(&digits(1122233)));
+        assert!(runs(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_rules() {
+        assert!(Rule::NonDecreasing.check(113359));
+        assert!(!Rule::NonDecreasing.check(113329));
+        assert!(Rule::RunOfExactly(3).check(12223333));
+        assert!(!Rule::RunOfExactly(3).check(1222233));
+        assert!(Rule::RunOfAtLeast(3).check(1222233));
+        assert!(!Rule::RunOfAtLeast(3).check(121212));
+        assert!(Rule::DigitSum(10..=12).check(55));
+        assert!(!Rule::DigitSum(10..=12).check(58));
+        assert!(Rule::Length(6).check(100000));
+        assert!(!Rule::Length(6).check(99999));
+        let even = Rule::custom(|digits| matches!(digits.last(), Some(digit) if digit % 2 == 0));
+        assert!(even.check(1234));
+        assert!(!even.check(1235));
+    }
+
+    #[test]
+    fn test_combining() {
+        let rule = Rule::NonDecreasing
+            .and(Rule::RunOfAtLeast(2))
+            .and(!Rule::DigitSum(0..=10));
+        assert!(rule.check(12277));
+        assert!(!rule.check(11224));
+        assert!(!rule.check(12345));
+        assert!(matches!(&rule, Rule::All(rules) if rules.len() == 3));
+
+        let either = Rule::RunOfExactly(4)
+            .or(Rule::Length(2))
+            .or(Rule::Length(3));
+        assert!(either.check(11112));
+        assert!(either.check(12));
+        assert!(either.check(123));
+        assert!(!either.check(1234));
+        assert!(matches!(!!Rule::NonDecreasing, Rule::NonDecreasing));
+        assert_eq!(
+            "All([NonDecreasing, Not(Length(6))])",
+            format!("{:?}", Rule::NonDecreasing.and(!Rule::Length(6)))
+        );
+    }
+
+    fn choose(n: u64, k: u64) -> u64 {
+        (0..k).fold(1, |product, i| product * (n - i) / (i + 1))
+    }
+
+    #[test]
+    fn test_count_matches_trying_each() {
+        for n in 1..4 {
+            let at_least = Rule::NonDecreasing.and(Rule::RunOfAtLeast(n));
+            let exactly = Rule::NonDecreasing.and(Rule::RunOfExactly(n));
+            for range in &[0..=0, 0..=9, 5..=150, 1000..=4321, 11122..=99999] {
+                assert_eq!(
+                    at_least.count(range.clone()) as u64,
+                    count_non_decreasing(range.clone(), Run::AtLeast(n)),
+                    "at least {} in {:?}",
+                    n,
+                    range
+                );
+                assert_eq!(
+                    exactly.count(range.clone()) as u64,
+                    count_non_decreasing(range.clone(), Run::Exactly(n)),
+                    "exactly {} in {:?}",
+                    n,
+                    range
+                );
+            }
+        }
+        assert_eq!(
+            0,
+            count_non_decreasing(RangeInclusive::new(10, 9), Run::AtLeast(2))
+        );
+    }
+
+    #[test]
+    fn test_count_wide() {
+        // non-decreasing numbers of a given length are multisets of the
+        // digits 1 to 9, and those without a double are sets of them
+        let all: u64 = 1 + (1..=12).map(|len| choose(len + 8, 8)).sum::<u64>();
+        let distinct: u64 = 1 + (1..=9).map(|len| choose(9, len)).sum::<u64>();
+        assert_eq!(
+            all,
+            count_non_decreasing(0..=999_999_999_999, Run::AtLeast(1))
+        );
+        assert_eq!(
+            all - distinct,
+            count_non_decreasing(0..=999_999_999_999, Run::AtLeast(2))
+        );
+        assert_eq!(
+            count_non_decreasing(0..=u64::MAX, Run::Exactly(2)),
+            count_non_decreasing(0..=999_999_999_999, Run::Exactly(2))
+                + count_non_decreasing(1_000_000_000_000..=u64::MAX, Run::Exactly(2))
+        );
+    }
+}
@@ -0,0 +1,1018 @@
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter;
+
+mod board;
+mod metric;
+mod svg;
+mod sweep;
+#[cfg(test)]
+mod testing;
+
+pub use board::{Board, Crossing};
+pub use metric::{Metric, UnknownMetric};
+use sweep::Meeting;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+
+    pub fn manhattan_distance(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+
+    /// Whichever of the two is nearer the origin, or the lower if they're
+    /// just as near.
+    pub fn min_by_distance(self, other: Point) -> Point {
+        cmp::min_by_key(self, other, |point| (point.manhattan_distance(), *point))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    BadDirection(String),
+    BadDistance(String),
+    /// The move takes the wire further than coordinates or step counts go.
+    OutOfRange(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::BadDirection(mv) => write!(f, "bad direction in move {:?}", mv),
+            ParseError::BadDistance(mv) => write!(f, "bad distance in move {:?}", mv),
+            ParseError::OutOfRange(mv) => write!(f, "move {:?} goes out of range", mv),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Move {
+    Right(u32),
+    Left(u32),
+    Up(u32),
+    Down(u32),
+    UpRight(u32),
+    UpLeft(u32),
+    DownRight(u32),
+    DownLeft(u32),
+}
+
+impl Move {
+    pub fn parse(mv: &str) -> Result<Move, ParseError> {
+        let split = mv
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(mv.len());
+        let direction: fn(u32) -> Move = match &mv[..split] {
+            "R" => Move::Right,
+            "L" => Move::Left,
+            "U" => Move::Up,
+            "D" => Move::Down,
+            "NE" => Move::UpRight,
+            "NW" => Move::UpLeft,
+            "SE" => Move::DownRight,
+            "SW" => Move::DownLeft,
+            _ => return Err(ParseError::BadDirection(mv.to_string())),
+        };
+        let distance = mv[split..]
+            .parse()
+            .ok()
+            .filter(|distance| i32::try_from(*distance).is_ok())
+            .ok_or_else(|| ParseError::BadDistance(mv.to_string()))?;
+        Ok(direction(distance))
+    }
+
+    /// Where the move ends up when made from `start`, unless that's out of
+    /// range. A diagonal move goes one across and one up or down with each
+    /// step.
+    fn destination(self, start: Point) -> Option<Point> {
+        let (dx, dy, distance): (i32, i32, u32) = match self {
+            Move::Right(distance) => (1, 0, distance),
+            Move::Left(distance) => (-1, 0, distance),
+            Move::Up(distance) => (0, 1, distance),
+            Move::Down(distance) => (0, -1, distance),
+            Move::UpRight(distance) => (1, 1, distance),
+            Move::UpLeft(distance) => (-1, 1, distance),
+            Move::DownRight(distance) => (1, -1, distance),
+            Move::DownLeft(distance) => (-1, -1, distance),
+        };
+        let distance = i32::try_from(distance).ok()?;
+        Some(Point::new(
+            start.x.checked_add(dx.checked_mul(distance)?)?,
+            start.y.checked_add(dy.checked_mul(distance)?)?,
+        ))
+    }
+}
+
+/// Where a straight piece of wire lies, with its ends in increasing order.
+/// Diagonal ones run at 45 degrees, along `y = x + c` when rising to the
+/// right and `y = c - x` when falling.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Segment {
+    Horizontal { x_1: i32, x_2: i32, y: i32 },
+    Vertical { y_1: i32, y_2: i32, x: i32 },
+    Rising { x_1: i32, x_2: i32, c: i64 },
+    Falling { x_1: i32, x_2: i32, c: i64 },
+}
+
+// the ways a segment can lie
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Direction {
+    Horizontal,
+    Vertical,
+    Rising,
+    Falling,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Horizontal,
+        Direction::Vertical,
+        Direction::Rising,
+        Direction::Falling,
+    ];
+
+    // (a, b) such that a * x + b * y is the same all along a line this way
+    fn coefficients(self) -> (i64, i64) {
+        match self {
+            Direction::Horizontal => (0, 1),
+            Direction::Vertical => (1, 0),
+            Direction::Rising => (-1, 1),
+            Direction::Falling => (1, 1),
+        }
+    }
+
+    // which of the lines this way `point` is on. Diagonal ones can be
+    // further out than any coordinate.
+    fn line(self, point: Point) -> i64 {
+        let (a, b) = self.coefficients();
+        a * i64::from(point.x) + b * i64::from(point.y)
+    }
+
+    // how far along its line `point` is: y on vertical lines, else x
+    fn along(self, point: Point) -> i32 {
+        match self {
+            Direction::Vertical => point.y,
+            _ => point.x,
+        }
+    }
+
+    // the point `along` a line, which must be on the grid
+    fn point(self, line: i64, along: i32) -> Point {
+        let other = match self {
+            Direction::Horizontal | Direction::Vertical => line,
+            Direction::Rising => line + i64::from(along),
+            Direction::Falling => line - i64::from(along),
+        };
+        let other = i32::try_from(other).expect("point off the grid");
+        match self {
+            Direction::Vertical => Point::new(other, along),
+            _ => Point::new(along, other),
+        }
+    }
+
+    // the grid point where `line` this way crosses `other_line` the other
+    // way, if they aren't parallel and don't pass between grid points
+    fn meet(self, line: i64, other: Direction, other_line: i64) -> Option<Point> {
+        let ((a, b), (c, d)) = (self.coefficients(), other.coefficients());
+        let det = a * d - b * c;
+        if det == 0 {
+            return None;
+        }
+        let (x, y) = (line * d - b * other_line, a * other_line - c * line);
+        if x % det != 0 || y % det != 0 {
+            return None;
+        }
+        let (x, y) = (i32::try_from(x / det).ok()?, i32::try_from(y / det).ok()?);
+        Some(Point::new(x, y))
+    }
+}
+
+impl Segment {
+    /// The segment between two points, which must be level, one above the
+    /// other, or diagonal to each other. A single point is vertical.
+    pub fn new(a: Point, b: Point) -> Self {
+        let (low, high) = (cmp::min(a, b), cmp::max(a, b));
+        let direction = if low.x == high.x {
+            Direction::Vertical
+        } else if low.y == high.y {
+            Direction::Horizontal
+        } else if Direction::Rising.line(low) == Direction::Rising.line(high) {
+            Direction::Rising
+        } else if Direction::Falling.line(low) == Direction::Falling.line(high) {
+            Direction::Falling
+        } else {
+            panic!("{:?} to {:?} isn't straight or diagonal", a, b);
+        };
+        let (line, x_1, x_2) = (direction.line(low), low.x, high.x);
+        match direction {
+            Direction::Horizontal => Segment::Horizontal { x_1, x_2, y: low.y },
+            Direction::Vertical => Segment::Vertical {
+                y_1: low.y,
+                y_2: high.y,
+                x: low.x,
+            },
+            Direction::Rising => Segment::Rising { x_1, x_2, c: line },
+            Direction::Falling => Segment::Falling { x_1, x_2, c: line },
+        }
+    }
+
+    // the part of `line` from `start` to `end` along it
+    fn on_line(direction: Direction, line: i64, start: i32, end: i32) -> Self {
+        Segment::new(direction.point(line, start), direction.point(line, end))
+    }
+
+    // the line the segment is on and where along it the segment starts and
+    // ends
+    fn span(self) -> (Direction, i64, i32, i32) {
+        match self {
+            Segment::Horizontal { x_1, x_2, y } => (Direction::Horizontal, y.into(), x_1, x_2),
+            Segment::Vertical { y_1, y_2, x } => (Direction::Vertical, x.into(), y_1, y_2),
+            Segment::Rising { x_1, x_2, c } => (Direction::Rising, c, x_1, x_2),
+            Segment::Falling { x_1, x_2, c } => (Direction::Falling, c, x_1, x_2),
+        }
+    }
+
+    /// The piece two segments share: the point where they cross, or the
+    /// overlap of two on the same line. Diagonals that cross between grid
+    /// points don't meet.
+    pub fn intersect(self, other: Self) -> Option<Segment> {
+        let (direction, line, start, end) = self.span();
+        let (other_direction, other_line, other_start, other_end) = other.span();
+        if direction == other_direction {
+            let (start, end) = (start.max(other_start), end.min(other_end));
+            if line != other_line || start > end {
+                return None;
+            }
+            return Some(Segment::on_line(direction, line, start, end));
+        }
+        let point = direction.meet(line, other_direction, other_line)?;
+        if self.contains(point) && other.contains(point) {
+            Some(Segment::new(point, point))
+        } else {
+            None
+        }
+    }
+
+    /// The lowest and the highest end.
+    pub fn ends(self) -> (Point, Point) {
+        let (direction, line, start, end) = self.span();
+        (direction.point(line, start), direction.point(line, end))
+    }
+
+    pub fn contains(self, point: Point) -> bool {
+        let (direction, line, start, end) = self.span();
+        let along = direction.along(point);
+        direction.line(point) == line && start <= along && along <= end
+    }
+
+    /// Every point on the segment, from its lowest end.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        let (start, end) = self.ends();
+        iter::once(start).chain(
+            Leg {
+                start,
+                end,
+                steps: 0,
+            }
+            .points(),
+        )
+    }
+
+    // the segment's ends, except that an end at the origin is swapped for
+    // the point next to it
+    fn ends_off_origin(self) -> impl Iterator<Item = Point> {
+        let (low, high) = self.ends();
+        let (dx, dy) = ((high.x - low.x).signum(), (high.y - low.y).signum());
+        let ends = if low == Point::ORIGIN {
+            vec![Point::new(dx, dy), high]
+        } else if high == Point::ORIGIN {
+            vec![low, Point::new(-dx, -dy)]
+        } else {
+            vec![low, high]
+        };
+        // a segment that's just the origin has no points left
+        ends.into_iter().filter(|point| *point != Point::ORIGIN)
+    }
+
+    /// The point on the segment nearest the origin, not counting the origin
+    /// itself.
+    pub fn nearest_point(self) -> Option<Point> {
+        Metric::Manhattan
+            .nearest(self, Point::ORIGIN)
+            .map(|(point, _)| point)
+    }
+}
+
+/// One straight run of a wire in the direction it was laid. `steps` is how
+/// far along the wire `start` is.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Leg {
+    pub start: Point,
+    pub end: Point,
+    pub steps: u32,
+}
+
+impl Leg {
+    pub fn segment(&self) -> Segment {
+        Segment::new(self.start, self.end)
+    }
+
+    /// How many steps the leg takes, diagonal ones included.
+    pub fn len(&self) -> u32 {
+        let (dx, dy) = (self.end.x - self.start.x, self.end.y - self.start.y);
+        dx.abs().max(dy.abs()) as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// How far along the wire `point` is, if it's on this leg.
+    pub fn steps_to(&self, point: Point) -> u32 {
+        let (dx, dy) = (point.x - self.start.x, point.y - self.start.y);
+        self.steps + dx.abs().max(dy.abs()) as u32
+    }
+
+    /// Every point the leg passes through after its start, in order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let dx = (self.end.x - self.start.x).signum();
+        let dy = (self.end.y - self.start.y).signum();
+        let start = self.start;
+        (1..=self.len() as i32).map(move |i| Point::new(start.x + i * dx, start.y + i * dy))
+    }
+}
+
+/// A stretch of wire that leaves `point` after `start` steps and is back
+/// there after `end`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Loop {
+    pub point: Point,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Loop {
+    pub fn len(&self) -> u32 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A wire laid from the origin by a list of moves like `R75,D30,U83`, or
+/// diagonal ones like `NE4` and `SW2`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Wire {
+    pub legs: Vec<Leg>,
+}
+
+impl Wire {
+    pub fn parse(input: &str) -> Result<Wire, ParseError> {
+        let mut legs = vec![];
+        let mut start = Point::ORIGIN;
+        let mut steps = 0;
+        for mv in input.trim().split(',').filter(|mv| !mv.is_empty()) {
+            let mv = mv.trim();
+            let out_of_range = || ParseError::OutOfRange(mv.to_string());
+            let end = Move::parse(mv)?
+                .destination(start)
+                .ok_or_else(out_of_range)?;
+            let leg = Leg { start, end, steps };
+            steps = steps.checked_add(leg.len()).ok_or_else(out_of_range)?;
+            legs.push(leg);
+            start = end;
+        }
+        Ok(Wire { legs })
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.legs.iter().map(Leg::segment)
+    }
+
+    /// How many steps it takes to first reach `point`, if the wire gets
+    /// there at all. Later visits never count: like the puzzle says, a wire
+    /// that comes back to a point still got there when it first did.
+    pub fn steps_to(&self, point: Point) -> Option<u32> {
+        let leg = self.legs.iter().find(|leg| leg.segment().contains(point))?;
+        Some(leg.steps_to(point))
+    }
+
+    /// Every time the wire is at `point`, in steps, from the first.
+    pub fn visits(&self, point: Point) -> Vec<u32> {
+        let mut visits: Vec<u32> = self
+            .legs
+            .iter()
+            .filter(|leg| leg.segment().contains(point))
+            .map(|leg| leg.steps_to(point))
+            .collect();
+        // a corner is on the legs both before and after it
+        visits.dedup();
+        visits
+    }
+
+    // every point the wire is at more than once, with all its visits
+    fn revisits(&self) -> BTreeMap<Point, BTreeSet<u32>> {
+        // each leg on its own, so legs of the same wire are checked
+        // against each other
+        let segments: Vec<(usize, Segment)> = self.segments().enumerate().collect();
+        let mut revisits: BTreeMap<Point, BTreeSet<u32>> = BTreeMap::new();
+        for (i, j, shared) in sweep::intersections(&segments) {
+            let (a, b) = (&self.legs[i], &self.legs[j]);
+            for point in shared.points() {
+                let (first, again) = (a.steps_to(point), b.steps_to(point));
+                // the same visit, at a corner
+                if first == again {
+                    continue;
+                }
+                let visits = revisits.entry(point).or_default();
+                visits.insert(first);
+                visits.insert(again);
+            }
+        }
+        revisits
+    }
+
+    /// Every point the wire comes back to after leaving it, whether by
+    /// crossing itself or running back along itself, in order of x then y.
+    /// The origin is one if the wire returns to it.
+    pub fn self_crossings(&self) -> Vec<Point> {
+        self.revisits().into_keys().collect()
+    }
+
+    /// The closed loops in the wire's path, in the order they close. Every
+    /// time the wire comes back to a point closes a loop starting from when
+    /// it was last there, so doubling back over `n` points closes `n` loops
+    /// inside each other.
+    pub fn loops(&self) -> Vec<Loop> {
+        let mut loops: Vec<Loop> = self
+            .revisits()
+            .into_iter()
+            .flat_map(|(point, visits)| {
+                let visits: Vec<u32> = visits.into_iter().collect();
+                (1..visits.len())
+                    .map(|i| Loop {
+                        point,
+                        start: visits[i - 1],
+                        end: visits[i],
+                    })
+                    .collect::<Vec<Loop>>()
+            })
+            .collect();
+        loops.sort_by_key(|found| (found.end, found.start));
+        loops
+    }
+
+    /// Every piece this wire shares with `other`: points where they cross
+    /// and stretches where they run along each other.
+    pub fn intersections(&self, other: &Wire) -> Vec<Segment> {
+        let (_, meetings) = meetings(&[self, other]);
+        meetings.into_iter().map(|(_, _, shared)| shared).collect()
+    }
+
+    /// Every point other than the origin where this wire meets `other`.
+    pub fn crossings(&self, other: &Wire) -> Vec<Point> {
+        let mut points: Vec<Point> = self
+            .intersections(other)
+            .into_iter()
+            .flat_map(Segment::points)
+            .filter(|point| *point != Point::ORIGIN)
+            .collect();
+        points.sort_by_key(|point| (point.x, point.y));
+        points.dedup();
+        points
+    }
+
+    /// The crossing with `other` nearest the origin.
+    pub fn closest_crossing(&self, other: &Wire) -> Option<Point> {
+        self.closest_crossing_by(other, &Metric::Manhattan, Point::ORIGIN)
+            .map(|(point, _)| point)
+    }
+
+    /// The crossing with `other` nearest `from` by `metric`, and how far it
+    /// is. Ties go to the lower point.
+    pub fn closest_crossing_by(
+        &self,
+        other: &Wire,
+        metric: &Metric,
+        from: Point,
+    ) -> Option<(Point, f64)> {
+        if let Metric::Steps = metric {
+            return self
+                .quickest_crossing(other)
+                .map(|(point, steps)| (point, steps as f64));
+        }
+        self.intersections(other)
+            .into_iter()
+            .filter_map(|shared| metric.nearest(shared, from))
+            .min_by(metric::closer)
+    }
+
+    /// The crossing both wires together take the fewest steps to reach, and
+    /// those steps, each wire counting only its first visit there.
+    pub fn quickest_crossing(&self, other: &Wire) -> Option<(Point, u64)> {
+        let (legs, meetings) = meetings(&[self, other]);
+        // the steps change linearly along a shared piece, so the fewest are
+        // at one of its ends
+        meetings
+            .into_iter()
+            .flat_map(|(i, j, shared)| {
+                let (legs_i, legs_j) = (legs[i].1, legs[j].1);
+                shared.ends_off_origin().map(move |point| {
+                    let steps =
+                        u64::from(legs_i.steps_to(point)) + u64::from(legs_j.steps_to(point));
+                    (point, steps)
+                })
+            })
+            .min_by_key(|(point, steps)| (*steps, *point))
+    }
+
+    /// The fewest steps both wires together take to reach a point where they
+    /// meet, each counting only its first visit there.
+    pub fn fewest_combined_steps(&self, other: &Wire) -> Option<u64> {
+        self.quickest_crossing(other).map(|(_, steps)| steps)
+    }
+}
+
+// the legs of all the wires, each with the index of its wire, and where legs
+// of different wires meet
+fn meetings<'a>(wires: &[&'a Wire]) -> (Vec<(usize, &'a Leg)>, Vec<Meeting>) {
+    let legs: Vec<(usize, &Leg)> = wires
+        .iter()
+        .enumerate()
+        .flat_map(|(i, wire)| wire.legs.iter().map(move |leg| (i, leg)))
+        .collect();
+    let segments: Vec<(usize, Segment)> = legs.iter().map(|(i, leg)| (*i, leg.segment())).collect();
+    (legs, sweep::intersections(&segments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::Random;
+    use super::*;
+    use std::collections::HashMap;
+
+    fn wire(input: &str) -> Wire {
+        Wire::parse(input).unwrap()
+    }
+
+    #[test]
+    fn test_point_sorting() {
+        let mut points = vec![
+            Point::new(1, 2),
+            Point::new(-2, -2),
+            Point::new(12, 15),
+            Point::new(5, 5),
+        ];
+        points.sort_by_key(Point::manhattan_distance);
+        assert_eq!(
+            points,
+            vec![
+                Point::new(1, 2),
+                Point::new(-2, -2),
+                Point::new(5, 5),
+                Point::new(12, 15)
+            ]
+        )
+    }
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!(Ok(Move::Right(12)), Move::parse("R12"));
+        assert_eq!(Ok(Move::Left(144)), Move::parse("L144"));
+        assert_eq!(Ok(Move::Up(1200)), Move::parse("U1200"));
+        assert_eq!(Ok(Move::Down(1)), Move::parse("D1"));
+        assert_eq!(Ok(Move::UpRight(3)), Move::parse("NE3"));
+        assert_eq!(Ok(Move::DownLeft(12)), Move::parse("SW12"));
+        assert_eq!(
+            Err(ParseError::BadDirection("N3".to_string())),
+            Move::parse("N3")
+        );
+        assert_eq!(
+            Err(ParseError::BadDirection("X1".to_string())),
+            Move::parse("X1")
+        );
+        assert_eq!(
+            Err(ParseError::BadDistance("R-1".to_string())),
+            Move::parse("R-1")
+        );
+        assert_eq!(
+            Err(ParseError::BadDistance("R3000000000".to_string())),
+            Move::parse("R3000000000")
+        );
+        assert_eq!(
+            Err(ParseError::OutOfRange("R2000000000".to_string())),
+            Wire::parse("R2000000000,L1,R2000000000")
+        );
+        assert_eq!(
+            Err(ParseError::OutOfRange("U2000000000".to_string())),
+            Wire::parse("U2000000000,D2000000000,U2000000000")
+        );
+        assert_eq!(
+            "bad distance in move \"U\"",
+            Wire::parse("R1,U").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_intersect() {
+        assert_eq!(
+            None,
+            Segment::Horizontal {
+                x_1: 0,
+                x_2: 2,
+                y: -3
+            }
+            .intersect(Segment::Horizontal {
+                x_1: 0,
+                x_2: 2,
+                y: -1
+            })
+        );
+        let point = Point::new(1, 2);
+        assert_eq!(
+            Some(Segment::new(point, point)),
+            Segment::Vertical {
+                y_1: 0,
+                y_2: 3,
+                x: 1
+            }
+            .intersect(Segment::Horizontal {
+                x_1: 0,
+                x_2: 2,
+                y: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_intersect_diagonals() {
+        let segment =
+            |a: (i32, i32), b: (i32, i32)| Segment::new(Point::new(a.0, a.1), Point::new(b.0, b.1));
+        let point = |x, y| Some(segment((x, y), (x, y)));
+        assert_eq!(
+            Segment::Rising {
+                x_1: -1,
+                x_2: 3,
+                c: 2
+            },
+            segment((3, 5), (-1, 1))
+        );
+        assert_eq!(
+            Segment::Falling {
+                x_1: 0,
+                x_2: 4,
+                c: 4
+            },
+            segment((0, 4), (4, 0))
+        );
+        assert_eq!(
+            point(2, 2),
+            segment((0, 0), (4, 4)).intersect(segment((0, 4), (4, 0)))
+        );
+        assert_eq!(
+            point(3, 3),
+            segment((0, 0), (4, 4)).intersect(segment((3, -1), (3, 7)))
+        );
+        assert_eq!(
+            point(1, 3),
+            segment((0, 4), (4, 0)).intersect(segment((-2, 3), (5, 3)))
+        );
+        // these cross between grid points, at (0.5, 0.5)
+        assert_eq!(
+            None,
+            segment((0, 0), (1, 1)).intersect(segment((0, 1), (1, 0)))
+        );
+        assert_eq!(
+            Some(segment((2, 2), (3, 3))),
+            segment((0, 0), (3, 3)).intersect(segment((5, 5), (2, 2)))
+        );
+        assert_eq!(
+            None,
+            segment((0, 0), (3, 3)).intersect(segment((0, 1), (3, 4)))
+        );
+    }
+
+    #[test]
+    fn test_diagonal_wires() {
+        let a = wire("NE4,R2,SW2");
+        assert_eq!(Point::new(4, 2), a.legs[2].end);
+        assert_eq!(6, a.legs[2].steps);
+        assert_eq!(Some(6), a.steps_to(Point::new(6, 4)));
+        let b = wire("R4,U2,NW3");
+        assert_eq!(vec![Point::new(3, 3), Point::new(4, 2)], a.crossings(&b));
+        assert_eq!(Some(Point::new(3, 3)), a.closest_crossing(&b));
+        assert_eq!(Some((Point::new(3, 3), 10)), a.quickest_crossing(&b));
+        // b's first diagonal passes a's between (2, 2) and (3, 3)
+        assert!(a.crossings(&wire("R4,U1,NW3")).is_empty());
+        // running back down its own diagonal
+        assert_eq!(
+            vec![Point::new(1, 1), Point::new(2, 2)],
+            wire("NE3,R1,SW1,L1,SW1").self_crossings()
+        );
+    }
+
+    #[test]
+    fn test_overlap() {
+        let horizontal = |x_1, x_2| Segment::Horizontal { x_1, x_2, y: 4 };
+        assert_eq!(
+            Some(horizontal(3, 5)),
+            horizontal(0, 5).intersect(horizontal(3, 9))
+        );
+        assert_eq!(
+            Some(Segment::new(Point::new(5, 4), Point::new(5, 4))),
+            horizontal(0, 5).intersect(horizontal(5, 9))
+        );
+        assert_eq!(None, horizontal(0, 5).intersect(horizontal(6, 9)));
+        let vertical = Segment::Vertical {
+            y_1: -3,
+            y_2: 2,
+            x: 7,
+        };
+        assert_eq!(Some(vertical), vertical.intersect(vertical));
+    }
+
+    // walks both wires a step at a time, for comparison
+    fn walked(a: &Wire, b: &Wire) -> (Option<i32>, Option<u64>) {
+        let walk = |wire: &Wire| {
+            let mut steps = HashMap::new();
+            for leg in &wire.legs {
+                for (i, point) in leg.points().enumerate() {
+                    steps.entry(point).or_insert(leg.steps + 1 + i as u32);
+                }
+            }
+            steps.remove(&Point::ORIGIN);
+            steps
+        };
+        let (a, b) = (walk(a), walk(b));
+        let both: Vec<(&Point, u64)> = a
+            .iter()
+            .filter_map(|(point, steps)| Some((point, u64::from(steps + b.get(point)?))))
+            .collect();
+        (
+            both.iter()
+                .map(|(point, _)| point.manhattan_distance())
+                .min(),
+            both.iter().map(|(_, steps)| *steps).min(),
+        )
+    }
+
+    #[test]
+    fn test_matches_walking() {
+        let mut random = Random(12345);
+        for _ in 0..100 {
+            let (a, b) = (random.wire(20), random.wire(20));
+            assert_eq!(
+                walked(&a, &b),
+                (
+                    a.closest_crossing(&b)
+                        .map(|point| point.manhattan_distance()),
+                    a.fewest_combined_steps(&b)
+                ),
+                "{:?} {:?}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn test_metrics_match_crossings() {
+        let mut random = Random(54321);
+        let metrics = vec![
+            Metric::Manhattan,
+            Metric::Chebyshev,
+            Metric::Euclidean,
+            Metric::custom(|from, to| f64::from((to.x - from.x) * 3 - to.y)),
+        ];
+        for _ in 0..100 {
+            let (a, b) = (random.wire(15), random.wire(15));
+            let from = Point::new(random.below(11) - 5, random.below(11) - 5);
+            for metric in &metrics {
+                let expected = a
+                    .crossings(&b)
+                    .into_iter()
+                    .map(|point| (point, metric.distance(from, point).unwrap()))
+                    .min_by(metric::closer);
+                assert_eq!(
+                    expected,
+                    a.closest_crossing_by(&b, metric, from),
+                    "{:?} from {:?}: {:?} {:?}",
+                    metric,
+                    from,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_self_crossings() {
+        // crosses itself at (3, 0) on the way back down
+        let a = wire("R5,U2,L2,D4");
+        assert_eq!(vec![Point::new(3, 0)], a.self_crossings());
+        assert_eq!(vec![3, 11], a.visits(Point::new(3, 0)));
+        assert_eq!(Some(3), a.steps_to(Point::new(3, 0)));
+        // corners aren't visited twice
+        assert_eq!(vec![5], a.visits(Point::new(5, 0)));
+        assert_eq!(
+            vec![Loop {
+                point: Point::new(3, 0),
+                start: 3,
+                end: 11
+            }],
+            a.loops()
+        );
+        // b meets a there after 5 steps, and 3 + 5 beats any other way
+        let b = wire("D1,R3,U2");
+        assert_eq!(Some(8), a.fewest_combined_steps(&b));
+        assert_eq!(Some(8), b.fewest_combined_steps(&a));
+
+        assert!(wire("R8,U5,L5,D3").self_crossings().is_empty());
+        assert!(wire("R8,U5,L5,D3").loops().is_empty());
+    }
+
+    #[test]
+    fn test_doubling_back() {
+        let back = wire("R5,L3,U1");
+        assert_eq!(
+            vec![Point::new(2, 0), Point::new(3, 0), Point::new(4, 0)],
+            back.self_crossings()
+        );
+        let loops: Vec<(i32, u32)> = back
+            .loops()
+            .iter()
+            .map(|found| (found.point.x, found.len()))
+            .collect();
+        assert_eq!(vec![(4, 2), (3, 4), (2, 6)], loops);
+        assert_eq!(vec![2, 8], back.visits(Point::new(2, 0)));
+
+        // the same stretch three times over
+        let zigzag = wire("R3,L3,R3");
+        assert_eq!(vec![0, 6], zigzag.visits(Point::ORIGIN));
+        assert_eq!(vec![1, 5, 7], zigzag.visits(Point::new(1, 0)));
+        assert_eq!(vec![3, 9], zigzag.visits(Point::new(3, 0)));
+        assert_eq!(
+            vec![
+                Point::ORIGIN,
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(3, 0)
+            ],
+            zigzag.self_crossings()
+        );
+    }
+
+    #[test]
+    fn test_closed_loop() {
+        // a square back to the origin, then on through it
+        let square = wire("R2,U2,L2,D4");
+        assert_eq!(vec![Point::ORIGIN], square.self_crossings());
+        assert_eq!(
+            vec![Loop {
+                point: Point::ORIGIN,
+                start: 0,
+                end: 8
+            }],
+            square.loops()
+        );
+        assert_eq!(Some(0), square.steps_to(Point::ORIGIN));
+    }
+
+    #[test]
+    fn test_long_wires() {
+        // far too long to walk one step at a time
+        let a = wire("R2000000000,U10");
+        let b = wire("U5,R2000000000,D5");
+        assert_eq!(Some(Point::new(2000000000, 0)), a.closest_crossing(&b));
+        assert_eq!(Some(4000000010), a.fewest_combined_steps(&b));
+
+        // out to the edge of the grid, where the lines diagonals are on and
+        // the steps both wires take together go further than an i32 or u32
+        let a = wire("D2147483647,D1,R5,NE3");
+        let b = wire("R3,D2147483647,D1,NW1");
+        assert_eq!(Some(Point::new(3, i32::MIN)), a.closest_crossing(&b));
+        assert_eq!(Some(2 * 2147483651), a.fewest_combined_steps(&b));
+        // y = x + 2^31 crossing y = -x
+        let rising = Segment::new(Point::new(i32::MIN, 0), Point::new(-1, i32::MAX));
+        let falling = Segment::new(
+            Point::new(i32::MIN + 1, i32::MAX),
+            Point::new(i32::MAX, i32::MIN + 1),
+        );
+        let crossing = Point::new(-1073741824, 1073741824);
+        assert_eq!(
+            Some(Segment::new(crossing, crossing)),
+            rising.intersect(falling)
+        );
+    }
+
+    #[test]
+    fn test_nearest_point() {
+        let segment =
+            |a: (i32, i32), b: (i32, i32)| Segment::new(Point::new(a.0, a.1), Point::new(b.0, b.1));
+        assert_eq!(
+            Some(Point::new(2, 0)),
+            segment((2, 0), (9, 0)).nearest_point()
+        );
+        assert_eq!(
+            Some(Point::new(3, -1)),
+            segment((3, -4), (3, -1)).nearest_point()
+        );
+        assert_eq!(
+            Some(Point::new(0, 3)),
+            segment((-5, 3), (4, 3)).nearest_point()
+        );
+        // the origin doesn't count, but its neighbours do
+        assert_eq!(
+            Some(Point::new(0, 1)),
+            segment((0, 0), (0, 6)).nearest_point()
+        );
+        assert_eq!(None, segment((0, 0), (0, 0)).nearest_point());
+    }
+
+    #[test]
+    fn test_legs() {
+        let path = wire("R8,U5,L5,D3");
+        assert_eq!(
+            Leg {
+                start: Point::new(8, 5),
+                end: Point::new(3, 5),
+                steps: 13
+            },
+            path.legs[2]
+        );
+        let points: Vec<Point> = path.legs[3].points().collect();
+        assert_eq!(
+            vec![Point::new(3, 4), Point::new(3, 3), Point::new(3, 2)],
+            points
+        );
+        assert_eq!(Some(16), path.steps_to(Point::new(5, 5)));
+        assert_eq!(None, path.steps_to(Point::new(5, 4)));
+        // the first visit counts
+        let looped = wire("R2,U1,L1,D2");
+        assert_eq!(Some(1), looped.steps_to(Point::new(1, 0)));
+    }
+
+    #[test]
+    fn test_crossings() {
+        let a = wire("R8,U5,L5,D3");
+        let b = wire("U7,R6,D4,L4");
+        let mut crossings = a.crossings(&b);
+        crossings.sort_by_key(Point::manhattan_distance);
+        assert_eq!(vec![Point::new(3, 3), Point::new(6, 5)], crossings);
+        assert_eq!(Some(Point::new(3, 3)), a.closest_crossing(&b));
+        assert_eq!(Some(30), a.fewest_combined_steps(&b));
+        // measured from elsewhere
+        let from = Point::new(7, 7);
+        assert_eq!(
+            Some((Point::new(6, 5), 3.0)),
+            a.closest_crossing_by(&b, &Metric::Manhattan, from)
+        );
+        // steps don't care where they're measured from
+        assert_eq!(
+            Some((Point::new(6, 5), 30.0)),
+            a.closest_crossing_by(&b, &Metric::Steps, from)
+        );
+
+        // running alongside counts as meeting, all the way along
+        let a = wire("U2,R6,D4");
+        let b = wire("R3,U2,R5");
+        assert_eq!(
+            vec![
+                Point::new(3, 2),
+                Point::new(4, 2),
+                Point::new(5, 2),
+                Point::new(6, 2)
+            ],
+            a.crossings(&b)
+        );
+        assert_eq!(Some(Point::new(3, 2)), a.closest_crossing(&b));
+        // including where both leave the origin the same way
+        assert_eq!(
+            Some(Point::new(1, 0)),
+            wire("R5,U1").closest_crossing(&wire("R2,D3"))
+        );
+
+        assert_eq!(Some(10), a.fewest_combined_steps(&b));
+        assert_eq!(Some((Point::new(3, 2), 10)), a.quickest_crossing(&b));
+        assert_eq!(Some(2), wire("R5,U1").fewest_combined_steps(&wire("R2,D3")));
+
+        let apart = wire("U3");
+        assert_eq!(None, apart.closest_crossing(&wire("D3")));
+        assert_eq!(None, apart.fewest_combined_steps(&wire("D3")));
+    }
+}
@@ -0,0 +1,236 @@
+use super::metric::closer;
+use super::{meetings, Metric, ParseError, Point, Wire};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Any number of wires, all laid from the same origin.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Board {
+    pub wires: Vec<Wire>,
+}
+
+/// A point other than the origin where wires meet, and the indices of those
+/// wires in increasing order.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Crossing {
+    pub point: Point,
+    pub wires: Vec<usize>,
+}
+
+impl Board {
+    /// One wire per line. Blank lines are skipped.
+    pub fn parse(input: &str) -> Result<Board, ParseError> {
+        let wires = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Wire::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Board { wires })
+    }
+
+    /// Every point where at least two wires meet, in order of x then y.
+    pub fn crossings(&self) -> Vec<Crossing> {
+        let wires: Vec<&Wire> = self.wires.iter().collect();
+        let (legs, meetings) = meetings(&wires);
+        let mut crossings: BTreeMap<Point, BTreeSet<usize>> = BTreeMap::new();
+        for (i, j, shared) in meetings {
+            for point in shared.points().filter(|point| *point != Point::ORIGIN) {
+                let wires = crossings.entry(point).or_default();
+                wires.insert(legs[i].0);
+                wires.insert(legs[j].0);
+            }
+        }
+        crossings
+            .into_iter()
+            .map(|(point, wires)| Crossing {
+                point,
+                wires: wires.into_iter().collect(),
+            })
+            .collect()
+    }
+
+    /// The points where at least `k` wires meet.
+    pub fn crossings_of(&self, k: usize) -> Vec<Crossing> {
+        let mut crossings = self.crossings();
+        crossings.retain(|crossing| crossing.wires.len() >= k);
+        crossings
+    }
+
+    /// For every pair of wires that meet, keyed by their indices in
+    /// increasing order, the crossing nearest the origin.
+    pub fn closest_crossings(&self) -> BTreeMap<(usize, usize), Point> {
+        self.closest_crossings_by(&Metric::Manhattan, Point::ORIGIN)
+            .into_iter()
+            .map(|(pair, (point, _))| (pair, point))
+            .collect()
+    }
+
+    /// For every pair of wires that meet, keyed by their indices in
+    /// increasing order, the crossing nearest `from` by `metric` and how
+    /// far it is.
+    pub fn closest_crossings_by(
+        &self,
+        metric: &Metric,
+        from: Point,
+    ) -> BTreeMap<(usize, usize), (Point, f64)> {
+        let mut closest = BTreeMap::new();
+        if let Metric::Steps = metric {
+            for (i, a) in self.wires.iter().enumerate() {
+                for (j, b) in self.wires.iter().enumerate().skip(i + 1) {
+                    if let Some(found) = a.closest_crossing_by(b, metric, from) {
+                        closest.insert((i, j), found);
+                    }
+                }
+            }
+            return closest;
+        }
+        let wires: Vec<&Wire> = self.wires.iter().collect();
+        let (legs, meetings) = meetings(&wires);
+        for (i, j, shared) in meetings {
+            let (a, b) = (legs[i].0, legs[j].0);
+            let found = match metric.nearest(shared, from) {
+                Some(found) => found,
+                None => continue,
+            };
+            let nearest = closest.entry((a.min(b), a.max(b))).or_insert(found);
+            if closer(&found, nearest) == Ordering::Less {
+                *nearest = found;
+            }
+        }
+        closest
+    }
+
+    /// The crossing of any two wires nearest the origin.
+    pub fn closest_crossing(&self) -> Option<Point> {
+        self.closest_crossing_by(&Metric::Manhattan, Point::ORIGIN)
+            .map(|(_, point, _)| point)
+    }
+
+    /// The crossing of any two wires nearest `from` by `metric`, with those
+    /// wires' indices and how far it is.
+    pub fn closest_crossing_by(
+        &self,
+        metric: &Metric,
+        from: Point,
+    ) -> Option<((usize, usize), Point, f64)> {
+        self.closest_crossings_by(metric, from)
+            .into_iter()
+            .min_by(|(_, a), (_, b)| closer(a, b))
+            .map(|(pair, (point, distance))| (pair, point, distance))
+    }
+
+    /// The crossing two wires take the fewest combined steps to reach, with
+    /// those wires' indices and the steps.
+    pub fn quickest_crossing(&self) -> Option<((usize, usize), Point, u64)> {
+        let mut quickest: Option<((usize, usize), Point, u64)> = None;
+        for (i, a) in self.wires.iter().enumerate() {
+            for (j, b) in self.wires.iter().enumerate().skip(i + 1) {
+                match (a.quickest_crossing(b), quickest) {
+                    (Some((_, steps)), Some((_, _, fewest))) if fewest <= steps => (),
+                    (Some((point, steps)), _) => quickest = Some(((i, j), point, steps)),
+                    (None, _) => (),
+                }
+            }
+        }
+        quickest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = "R8,U5,L5,D3
+U7,R6,D4,L4
+
+U3,R8
+";
+
+    #[test]
+    fn test_crossings() {
+        let board = Board::parse(BOARD).unwrap();
+        let crossing = |x, y, wires: &[usize]| Crossing {
+            point: Point::new(x, y),
+            wires: wires.to_vec(),
+        };
+        assert_eq!(
+            vec![
+                crossing(0, 1, &[1, 2]),
+                crossing(0, 2, &[1, 2]),
+                crossing(0, 3, &[1, 2]),
+                crossing(2, 3, &[1, 2]),
+                crossing(3, 3, &[0, 1, 2]),
+                crossing(4, 3, &[1, 2]),
+                crossing(5, 3, &[1, 2]),
+                crossing(6, 3, &[1, 2]),
+                crossing(6, 5, &[0, 1]),
+                crossing(8, 3, &[0, 2]),
+            ],
+            board.crossings()
+        );
+        assert_eq!(vec![crossing(3, 3, &[0, 1, 2])], board.crossings_of(3));
+        assert!(board.crossings_of(4).is_empty());
+    }
+
+    #[test]
+    fn test_closest_crossings() {
+        let board = Board::parse(BOARD).unwrap();
+        let closest: Vec<((usize, usize), Point)> = board.closest_crossings().into_iter().collect();
+        assert_eq!(
+            vec![
+                ((0, 1), Point::new(3, 3)),
+                ((0, 2), Point::new(3, 3)),
+                ((1, 2), Point::new(0, 1)),
+            ],
+            closest
+        );
+        assert_eq!(Some(Point::new(0, 1)), board.closest_crossing());
+        assert_eq!(
+            Some(((1, 2), Point::new(0, 1), 2)),
+            board.quickest_crossing()
+        );
+        // the same as asking the wires two at a time
+        let wires = &board.wires;
+        assert_eq!(wires[1].closest_crossing(&wires[2]), Some(closest[2].1));
+    }
+
+    #[test]
+    fn test_closest_crossings_by() {
+        let board = Board::parse(BOARD).unwrap();
+        let from = Point::new(9, 2);
+        let closest: Vec<((usize, usize), (Point, f64))> = board
+            .closest_crossings_by(&Metric::Chebyshev, from)
+            .into_iter()
+            .collect();
+        assert_eq!(
+            vec![
+                ((0, 1), (Point::new(6, 5), 3.0)),
+                ((0, 2), (Point::new(8, 3), 1.0)),
+                ((1, 2), (Point::new(6, 3), 3.0)),
+            ],
+            closest
+        );
+        assert_eq!(
+            Some(((0, 2), Point::new(8, 3), 2.0)),
+            board.closest_crossing_by(&Metric::Manhattan, from)
+        );
+        // a tie goes to the lower point
+        assert_eq!(
+            Some(((0, 1), Point::new(6, 5), 2.0)),
+            board.closest_crossing_by(&Metric::Euclidean, Point::new(8, 5))
+        );
+        assert_eq!(
+            Some(((1, 2), Point::new(0, 1), 2.0)),
+            board.closest_crossing_by(&Metric::Steps, from)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(3, Board::parse(BOARD).unwrap().wires.len());
+        assert_eq!(
+            Err(ParseError::BadDirection("Q4".to_string())),
+            Board::parse("R1\nQ4")
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use super::{Point, Segment};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// How to measure which crossing is closest.
+pub enum Metric {
+    /// |dx| + |dy|
+    Manhattan,
+    /// The larger of |dx| and |dy|.
+    Chebyshev,
+    /// Straight-line distance.
+    Euclidean,
+    /// The steps both wires together take to reach the crossing, each
+    /// counting only its first visit there. Where it's measured from is
+    /// ignored.
+    Steps,
+    /// Any other distance from the first point to the second. Every point
+    /// of a stretch two wires share is tried, so this is slow where they run
+    /// along each other for long.
+    Custom(Box<dyn Fn(Point, Point) -> f64>),
+}
+
+impl Metric {
+    pub fn custom(distance: impl Fn(Point, Point) -> f64 + 'static) -> Metric {
+        Metric::Custom(Box::new(distance))
+    }
+
+    /// How far `to` is from `from`, or `None` for `Steps`, which depends on
+    /// the wires rather than the points.
+    pub fn distance(&self, from: Point, to: Point) -> Option<f64> {
+        let (dx, dy) = (
+            (i64::from(to.x) - i64::from(from.x)).abs(),
+            (i64::from(to.y) - i64::from(from.y)).abs(),
+        );
+        match self {
+            Metric::Manhattan => Some((dx + dy) as f64),
+            Metric::Chebyshev => Some(dx.max(dy) as f64),
+            Metric::Euclidean => Some((dx as f64).hypot(dy as f64)),
+            Metric::Steps => None,
+            Metric::Custom(distance) => Some(distance(from, to)),
+        }
+    }
+
+    /// The point of `shared` other than the origin nearest `from`, and how
+    /// far it is. Always `None` for `Steps`.
+    pub(super) fn nearest(&self, shared: Segment, from: Point) -> Option<(Point, f64)> {
+        let distance = |point| (point, self.distance(from, point).unwrap());
+        match self {
+            Metric::Steps => return None,
+            Metric::Custom(_) => {
+                return shared
+                    .points()
+                    .filter(|point| *point != Point::ORIGIN)
+                    .map(distance)
+                    .min_by(closer)
+            }
+            _ => (),
+        }
+
+        // these are convex along the segment, so the lowest of the nearest
+        // points is the first that's no further than the one after it
+        let (low, high) = shared.ends();
+        let (dx, dy) = ((high.x - low.x).signum(), (high.y - low.y).signum());
+        let along = |k: i32| Point::new(low.x + k * dx, low.y + k * dy);
+        let len = (high.x - low.x).max(high.y - low.y).max(low.y - high.y);
+        let (mut first, mut last) = (0, len);
+        while first < last {
+            let middle = first + (last - first) / 2;
+            if distance(along(middle)).1 <= distance(along(middle + 1)).1 {
+                last = middle;
+            } else {
+                first = middle + 1;
+            }
+        }
+        if along(first) != Point::ORIGIN {
+            return Some(distance(along(first)));
+        }
+        // the origin doesn't count, and the nearest of the rest is next to it
+        [first - 1, first + 1]
+            .iter()
+            .filter(|k| (0..=len).contains(*k))
+            .map(|k| distance(along(*k)))
+            .min_by(closer)
+    }
+}
+
+/// Orders points with their distances by distance, then by the points.
+pub(super) fn closer(a: &(Point, f64), b: &(Point, f64)) -> Ordering {
+    a.1.total_cmp(&b.1).then(a.0.cmp(&b.0))
+}
+
+impl fmt::Debug for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Metric::Manhattan => write!(f, "Manhattan"),
+            Metric::Chebyshev => write!(f, "Chebyshev"),
+            Metric::Euclidean => write!(f, "Euclidean"),
+            Metric::Steps => write!(f, "Steps"),
+            Metric::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownMetric(pub String);
+
+impl fmt::Display for UnknownMetric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown metric {:?}, expected manhattan, chebyshev, euclidean or steps",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Metric {
+    type Err = UnknownMetric;
+
+    fn from_str(name: &str) -> Result<Metric, UnknownMetric> {
+        match name {
+            "manhattan" => Ok(Metric::Manhattan),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            "euclidean" => Ok(Metric::Euclidean),
+            "steps" => Ok(Metric::Steps),
+            _ => Err(UnknownMetric(name.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let (from, to) = (Point::new(1, -1), Point::new(4, 3));
+        assert_eq!(Some(7.0), Metric::Manhattan.distance(from, to));
+        assert_eq!(Some(4.0), Metric::Chebyshev.distance(from, to));
+        assert_eq!(Some(5.0), Metric::Euclidean.distance(from, to));
+        assert_eq!(None, Metric::Steps.distance(from, to));
+        let climb = Metric::custom(|from, to| f64::from(to.y - from.y));
+        assert_eq!(Some(4.0), climb.distance(from, to));
+
+        // as far apart as points get
+        let (from, to) = (
+            Point::new(i32::MIN, i32::MIN),
+            Point::new(i32::MAX, i32::MAX),
+        );
+        let across = 2f64.powi(32) - 1.0;
+        assert_eq!(Some(across * 2.0), Metric::Manhattan.distance(from, to));
+        let euclidean = Metric::Euclidean.distance(from, to).unwrap();
+        assert!((euclidean - across * 2f64.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let shared = Segment::new(Point::new(-3, 0), Point::new(5, 0));
+        assert_eq!(
+            Some((Point::new(-1, 0), 1.0)),
+            Metric::Manhattan.nearest(shared, Point::ORIGIN)
+        );
+        assert_eq!(
+            Some((Point::new(5, 0), 2.0)),
+            Metric::Chebyshev.nearest(shared, Point::new(7, 2))
+        );
+        assert_eq!(
+            Some((Point::new(2, 0), 3.0)),
+            Metric::Euclidean.nearest(shared, Point::new(2, 3))
+        );
+        // everywhere from x = -2 on is 5 away, and the lowest of those wins
+        assert_eq!(
+            Some((Point::new(-2, 0), 5.0)),
+            Metric::Chebyshev.nearest(shared, Point::new(3, 5))
+        );
+        assert_eq!(
+            Some((Point::new(1, 0), 9.0)),
+            Metric::Chebyshev.nearest(
+                Segment::new(Point::ORIGIN, Point::new(5, 0)),
+                Point::new(2, 9)
+            )
+        );
+        let rightmost = Metric::custom(|_, to| -f64::from(to.x));
+        assert_eq!(
+            Some((Point::new(5, 0), -5.0)),
+            rightmost.nearest(shared, Point::ORIGIN)
+        );
+        assert_eq!(None, Metric::Steps.nearest(shared, Point::ORIGIN));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(matches!("euclidean".parse(), Ok(Metric::Euclidean)));
+        assert!(matches!("steps".parse(), Ok(Metric::Steps)));
+        assert_eq!(
+            Some(UnknownMetric("taxicab".to_string())),
+            "taxicab".parse::<Metric>().err()
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use super::{Board, Metric, Point};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const COLORS: &[&str] = &[
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2", "#17becf", "#bcbd22",
+];
+
+// size of the drawing in pixels, with the legend below it
+const SIZE: usize = 800;
+const LINE: usize = 18;
+
+// y grows downwards in SVG, but up is up in the puzzle
+fn coords(point: Point) -> String {
+    format!("{},{}", point.x, -i64::from(point.y))
+}
+
+fn marker(svg: &mut String, point: Point, radius: f64, style: &str, title: &str) {
+    writeln!(
+        svg,
+        r#"    <circle cx="{}" cy="{}" r="{:.2}" {}><title>{}</title></circle>"#,
+        point.x,
+        -i64::from(point.y),
+        radius,
+        style,
+        title
+    )
+    .unwrap();
+}
+
+impl Board {
+    /// Draws the wires as an SVG image: each wire a coloured line, the
+    /// origin a black dot, every crossing a grey one, and the crossings
+    /// nearest the origin and fewest steps away ringed in red and blue.
+    /// Hovering over anything says what it is.
+    pub fn svg(&self) -> String {
+        let points: Vec<Point> = self
+            .wires
+            .iter()
+            .flat_map(|wire| wire.legs.iter().map(|leg| leg.end))
+            .chain(Some(Point::ORIGIN))
+            .collect();
+        let min_x = points.iter().map(|point| point.x).min().unwrap();
+        let max_x = points.iter().map(|point| point.x).max().unwrap();
+        let min_y = points.iter().map(|point| point.y).min().unwrap();
+        let max_y = points.iter().map(|point| point.y).max().unwrap();
+        // in floating point, since the span can be more than an i32 holds
+        let [min_x, max_x, min_y, max_y] = [min_x, max_x, min_y, max_y].map(f64::from);
+        let extent = (max_x - min_x).max(max_y - min_y).max(1.0);
+        let margin = extent / 20.0;
+        let radius = extent / 150.0;
+
+        let closest = self.closest_crossing();
+        let quickest = self.quickest_crossing();
+        let mut legend = vec![];
+        for (i, _) in self.wires.iter().enumerate() {
+            legend.push((COLORS[i % COLORS.len()], format!("wire {}", i)));
+        }
+        legend.push((
+            "red",
+            match closest {
+                Some(point) => format!(
+                    "nearest crossing ({}, {}), distance {}",
+                    point.x,
+                    point.y,
+                    Metric::Manhattan.distance(Point::ORIGIN, point).unwrap()
+                ),
+                None => "no crossings".to_string(),
+            },
+        ));
+        if let Some(((a, b), point, steps)) = quickest {
+            legend.push((
+                "blue",
+                format!(
+                    "fewest steps ({}, {}), {} along wires {} and {}",
+                    point.x, point.y, steps, a, b
+                ),
+            ));
+        }
+
+        let mut svg = String::new();
+        let height = SIZE + LINE * (legend.len() + 1);
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            SIZE, height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <rect width="{}" height="{}" fill="white"/>"#,
+            SIZE, height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <svg width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            SIZE,
+            SIZE,
+            min_x - margin,
+            -max_y - margin,
+            max_x - min_x + 2.0 * margin,
+            max_y - min_y + 2.0 * margin
+        )
+        .unwrap();
+        for (i, wire) in self.wires.iter().enumerate() {
+            let path: Vec<String> = Some(Point::ORIGIN)
+                .into_iter()
+                .chain(wire.legs.iter().map(|leg| leg.end))
+                .map(coords)
+                .collect();
+            writeln!(
+                svg,
+                r#"    <polyline points="{}" fill="none" stroke="{}" stroke-width="2" vector-effect="non-scaling-stroke"><title>wire {}</title></polyline>"#,
+                path.join(" "),
+                COLORS[i % COLORS.len()],
+                i
+            )
+            .unwrap();
+        }
+        for crossing in self.crossings() {
+            let wires: Vec<String> = crossing.wires.iter().map(usize::to_string).collect();
+            let title = format!(
+                "({}, {}): wires {}",
+                crossing.point.x,
+                crossing.point.y,
+                wires.join(", ")
+            );
+            marker(&mut svg, crossing.point, radius, r#"fill="grey""#, &title);
+        }
+        marker(
+            &mut svg,
+            Point::ORIGIN,
+            radius * 1.5,
+            r#"fill="black""#,
+            "origin",
+        );
+        let ring = |color| {
+            format!(
+                r#"fill="none" stroke="{}" stroke-width="2" vector-effect="non-scaling-stroke""#,
+                color
+            )
+        };
+        if let Some(point) = closest {
+            marker(
+                &mut svg,
+                point,
+                radius * 3.0,
+                &ring("red"),
+                &legend[self.wires.len()].1,
+            );
+        }
+        if let Some((_, point, _)) = quickest {
+            marker(
+                &mut svg,
+                point,
+                radius * 4.0,
+                &ring("blue"),
+                &legend[self.wires.len() + 1].1,
+            );
+        }
+        writeln!(svg, "  </svg>").unwrap();
+
+        for (i, (color, text)) in legend.iter().enumerate() {
+            let y = SIZE + LINE * (i + 1);
+            writeln!(
+                svg,
+                r#"  <rect x="10" y="{}" width="12" height="12" fill="{}"/>"#,
+                y - 11,
+                color
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                r#"  <text x="30" y="{}" font-family="monospace" font-size="13">{}</text>"#,
+                y, text
+            )
+            .unwrap();
+        }
+        writeln!(svg, "</svg>").unwrap();
+        svg
+    }
+
+    pub fn save_svg<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.svg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_svg() {
+        let board = Board::parse("R8,U5,L5,D3\nU7,R6,D4,L4").unwrap();
+        let svg = board.svg();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(
+            r##"<polyline points="0,0 8,0 8,-5 3,-5 3,-2" fill="none" stroke="#1f77b4""##
+        ));
+        assert!(svg.contains(
+            r##"<polyline points="0,0 0,-7 6,-7 6,-3 2,-3" fill="none" stroke="#ff7f0e""##
+        ));
+        assert!(svg.contains("<title>origin</title>"));
+        assert!(svg.contains("<title>(3, 3): wires 0, 1</title>"));
+        assert!(svg.contains("<title>(6, 5): wires 0, 1</title>"));
+        assert!(svg.contains("nearest crossing (3, 3), distance 6"));
+        assert!(svg.contains("fewest steps (6, 5), 30 along wires 0 and 1"));
+        // two crossings, the origin and two highlights
+        assert_eq!(5, svg.matches("<circle").count());
+
+        let path = env::temp_dir().join(format!("wires-{}.svg", std::process::id()));
+        board.save_svg(&path).unwrap();
+        assert_eq!(svg, fs::read_to_string(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_far_out() {
+        let board = Board::parse("D2147483647,D1,R5\nR3,D2147483647,D1").unwrap();
+        let svg = board.svg();
+        assert!(svg.contains(r#"<polyline points="0,0 0,2147483647 0,2147483648 5,2147483648""#));
+        assert!(svg.contains("nearest crossing (3, -2147483648), distance 2147483651"));
+    }
+
+    #[test]
+    fn test_no_crossings() {
+        let svg = Board::parse("U3\nD3").unwrap().svg();
+        assert!(svg.contains("no crossings"));
+        assert!(!svg.contains("fewest steps"));
+        assert_eq!(1, svg.matches("<circle").count());
+    }
+}
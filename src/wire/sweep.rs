@@ -0,0 +1,233 @@
+use super::{Direction, Segment};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
+
+// where a segment starts and ends along its line, and its index
+type Interval = (i32, i32, usize);
+
+/// The indices of two segments that meet and the piece they share.
+pub type Meeting = (usize, usize, Segment);
+
+/// Finds everywhere two segments with different tags meet, as the indices
+/// of the two segments and the piece they share: a single point where they
+/// cross, or the overlap of collinear ones.
+///
+/// Crossings are found by sweeping across x, keeping the horizontal
+/// segments the sweep is inside ordered by y so each vertical one only
+/// looks at those it crosses. Segments lying any other two ways are mapped
+/// to horizontal and vertical ones first. Overlaps can only happen between
+/// segments on the same line, so those are grouped by line and swept
+/// separately. Both take O((n + k) log n) for n segments and k meetings.
+pub fn intersections(segments: &[(usize, Segment)]) -> Vec<Meeting> {
+    let mut found = vec![];
+    for (n, across) in Direction::ALL.iter().enumerate() {
+        for up in &Direction::ALL[n + 1..] {
+            crossings_between(segments, *across, *up, &mut found);
+        }
+    }
+
+    let mut lines: HashMap<(Direction, i64), Vec<Interval>> = HashMap::new();
+    for (i, (_, segment)) in segments.iter().enumerate() {
+        let (direction, line, start, end) = segment.span();
+        lines
+            .entry((direction, line))
+            .or_default()
+            .push((start, end, i));
+    }
+    for ((direction, line), mut intervals) in lines {
+        intervals.sort_unstable();
+        overlaps(segments, &intervals, &mut found, |a, b| {
+            Segment::on_line(direction, line, a, b)
+        });
+    }
+    found
+}
+
+// Finds where segments lying `across` cross those lying `up`. Each point
+// is mapped to which line `up` and which line `across` it's on, which makes
+// the first horizontal and the second vertical, and crossings are mapped
+// back unless they fall between grid points.
+fn crossings_between(
+    segments: &[(usize, Segment)],
+    across: Direction,
+    up: Direction,
+    found: &mut Vec<Meeting>,
+) {
+    let map = |point| (up.line(point), across.line(point));
+    let mut indices = vec![];
+    let mut mapped = vec![];
+    for (i, (tag, segment)) in segments.iter().enumerate() {
+        let (direction, line, _, _) = segment.span();
+        let (low, high) = segment.ends();
+        let ((a_x, a_y), (b_x, b_y)) = (map(low), map(high));
+        let segment = if direction == across {
+            Mapped::Horizontal {
+                x_1: a_x.min(b_x),
+                x_2: a_x.max(b_x),
+                y: line,
+            }
+        } else if direction == up {
+            Mapped::Vertical {
+                y_1: a_y.min(b_y),
+                y_2: a_y.max(b_y),
+                x: line,
+            }
+        } else {
+            continue;
+        };
+        indices.push(i);
+        mapped.push((*tag, segment));
+    }
+
+    let mut crossed = vec![];
+    crossings(&mapped, &mut crossed);
+    for (j, i, x, y) in crossed {
+        if let Some(point) = across.meet(y, up, x) {
+            found.push((indices[j], indices[i], Segment::new(point, point)));
+        }
+    }
+}
+
+// A segment mapped to a horizontal or vertical one. The lines diagonals are
+// on can be further out than a `Point` goes.
+#[derive(Copy, Clone)]
+enum Mapped {
+    Horizontal { x_1: i64, x_2: i64, y: i64 },
+    Vertical { y_1: i64, y_2: i64, x: i64 },
+}
+
+// where horizontal segments cross vertical ones, as the indices of the two
+// and the point they cross at
+fn crossings(segments: &[(usize, Mapped)], found: &mut Vec<(usize, usize, i64, i64)>) {
+    // at the same x, horizontal segments start before and end after the
+    // vertical ones there are checked, so touching ends count
+    const START: u8 = 0;
+    const CHECK: u8 = 1;
+    const END: u8 = 2;
+    let mut events = vec![];
+    for (i, (_, segment)) in segments.iter().enumerate() {
+        match *segment {
+            Mapped::Horizontal { x_1, x_2, .. } => {
+                events.push((x_1, START, i));
+                events.push((x_2, END, i));
+            }
+            Mapped::Vertical { x, .. } => events.push((x, CHECK, i)),
+        }
+    }
+    events.sort_unstable();
+
+    // (y, index) of the horizontal segments the sweep is inside, by tag
+    let mut active: HashMap<usize, BTreeSet<(i64, usize)>> = HashMap::new();
+    for (x, kind, i) in events {
+        let (tag, segment) = segments[i];
+        match (kind, segment) {
+            (START, Mapped::Horizontal { y, .. }) => {
+                active.entry(tag).or_default().insert((y, i));
+            }
+            (END, Mapped::Horizontal { y, .. }) => {
+                active.get_mut(&tag).unwrap().remove(&(y, i));
+            }
+            (_, Mapped::Vertical { y_1, y_2, .. }) => {
+                for (other, horizontals) in &active {
+                    if *other == tag {
+                        continue;
+                    }
+                    for (y, j) in horizontals.range((y_1, 0)..=(y_2, usize::MAX)) {
+                        found.push((*j, i, x, *y));
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Reports overlapping pairs among `intervals` on one line, sorted by start.
+fn overlaps(
+    segments: &[(usize, Segment)],
+    intervals: &[Interval],
+    found: &mut Vec<Meeting>,
+    segment: impl Fn(i32, i32) -> Segment,
+) {
+    // (end, index) of the intervals that haven't ended yet
+    let mut active: BinaryHeap<(Reverse<i32>, usize)> = BinaryHeap::new();
+    for &(start, end, i) in intervals {
+        while matches!(active.peek(), Some((Reverse(other_end), _)) if *other_end < start) {
+            active.pop();
+        }
+        for (Reverse(other_end), j) in &active {
+            if segments[*j].0 != segments[i].0 {
+                found.push((*j, i, segment(start, end.min(*other_end))));
+            }
+        }
+        active.push((Reverse(end), i));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::testing::Random;
+    use crate::wire::Point;
+
+    fn normalized(mut found: Vec<Meeting>) -> Vec<Meeting> {
+        for (i, j, _) in &mut found {
+            if i > j {
+                std::mem::swap(i, j);
+            }
+        }
+        found.sort_by_key(|(i, j, _)| (*i, *j));
+        found
+    }
+
+    fn pairwise(segments: &[(usize, Segment)]) -> Vec<Meeting> {
+        let mut found = vec![];
+        for (i, (tag_i, a)) in segments.iter().enumerate() {
+            for (j, (tag_j, b)) in segments.iter().enumerate().skip(i + 1) {
+                if tag_i != tag_j {
+                    if let Some(shared) = a.intersect(*b) {
+                        found.push((i, j, shared));
+                    }
+                }
+            }
+        }
+        normalized(found)
+    }
+
+    #[test]
+    fn test_matches_pairwise() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        for _ in 0..50 {
+            // short moves on a small grid, so there's lots of overlap
+            let segments: Vec<(usize, Segment)> = (0..40)
+                .map(|_| {
+                    let a = Point::new(random.below(10), random.below(10));
+                    let b = if random.below(2) == 0 {
+                        Point::new(random.below(10), a.y)
+                    } else {
+                        Point::new(a.x, random.below(10))
+                    };
+                    (random.below(3) as usize, Segment::new(a, b))
+                })
+                .collect();
+            assert_eq!(pairwise(&segments), normalized(intersections(&segments)));
+        }
+    }
+
+    #[test]
+    fn test_diagonals_match_pairwise() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..50 {
+            let segments: Vec<(usize, Segment)> = (0..40)
+                .map(|_| {
+                    let a = Point::new(random.below(10), random.below(10));
+                    let (dx, dy) = [(1, 0), (0, 1), (1, 1), (1, -1)][random.below(4) as usize];
+                    let len = random.below(6);
+                    let b = Point::new(a.x + dx * len, a.y + dy * len);
+                    (random.below(3) as usize, Segment::new(a, b))
+                })
+                .collect();
+            assert_eq!(pairwise(&segments), normalized(intersections(&segments)));
+        }
+    }
+}
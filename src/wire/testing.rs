@@ -0,0 +1,24 @@
+use super::Wire;
+
+const DIRECTIONS: [&str; 8] = ["R", "L", "U", "D", "NE", "NW", "SE", "SW"];
+
+// a deterministic stand-in for random numbers
+pub struct Random(pub u64);
+
+impl Random {
+    pub fn below(&mut self, n: u64) -> i32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n) as i32
+    }
+
+    // a wire of `len` short moves every which way, so it crosses itself
+    // and others a lot
+    pub fn wire(&mut self, len: usize) -> Wire {
+        let moves: Vec<String> = (0..len)
+            .map(|_| format!("{}{}", DIRECTIONS[self.below(8) as usize], self.below(6)))
+            .collect();
+        Wire::parse(&moves.join(",")).unwrap()
+    }
+}